@@ -0,0 +1,206 @@
+use super::{Display, WindowBackend};
+use crate::window_manager::WindowEvent;
+use crate::{Rect, Result, Window, WindowId};
+use log::debug;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+const HEADLESS_DISPLAY_ID: u32 = 1;
+
+#[derive(Debug, Default)]
+struct HeadlessState {
+    windows: Vec<Window>,
+    scripted_events: VecDeque<WindowEvent>,
+}
+
+/// In-memory window system used when no real display server is available.
+///
+/// Windows and displays live in memory, and scripted events are replayed into
+/// the window manager's event channel when monitoring starts. Clones share the
+/// same state, so a test can keep a handle after boxing one for the manager.
+#[derive(Clone)]
+pub struct HeadlessBackend {
+    state: Arc<Mutex<HeadlessState>>,
+    event_sender: mpsc::Sender<WindowEvent>,
+    displays: HashMap<u32, Display>,
+}
+
+impl HeadlessBackend {
+    pub fn new(event_sender: mpsc::Sender<WindowEvent>) -> Self {
+        let mut displays = HashMap::new();
+        displays.insert(
+            HEADLESS_DISPLAY_ID,
+            Display {
+                id: HEADLESS_DISPLAY_ID,
+                rect: Rect::new(0.0, 0.0, 1920.0, 1080.0),
+                is_main: true,
+                name: "Headless Display".to_string(),
+            },
+        );
+
+        Self {
+            state: Arc::new(Mutex::new(HeadlessState::default())),
+            event_sender,
+            displays,
+        }
+    }
+
+    pub fn with_display(mut self, display: Display) -> Self {
+        if display.is_main {
+            for existing in self.displays.values_mut() {
+                existing.is_main = false;
+            }
+        }
+        self.displays.insert(display.id, display);
+        self
+    }
+
+    pub fn add_window(&self, window: Window) {
+        let mut state = self.state.lock().unwrap();
+        state.windows.retain(|w| w.id != window.id);
+        state.windows.push(window);
+    }
+
+    pub fn window(&self, window_id: WindowId) -> Option<Window> {
+        let state = self.state.lock().unwrap();
+        state.windows.iter().find(|w| w.id == window_id).cloned()
+    }
+
+    pub fn windows(&self) -> Vec<Window> {
+        self.state.lock().unwrap().windows.clone()
+    }
+
+    /// Queue an event to be replayed by `start_monitoring` or `replay_events`.
+    pub fn script_event(&self, event: WindowEvent) {
+        self.state.lock().unwrap().scripted_events.push_back(event);
+    }
+
+    /// Apply every queued event to the in-memory state and forward it to the
+    /// window manager. Returns the number of events replayed.
+    pub fn replay_events(&self) -> usize {
+        let mut replayed = 0;
+
+        loop {
+            let event = {
+                let mut state = self.state.lock().unwrap();
+                match state.scripted_events.pop_front() {
+                    Some(event) => {
+                        Self::apply_event(&mut state, &event);
+                        event
+                    }
+                    None => break,
+                }
+            };
+
+            debug!("Replaying headless event: {:?}", event);
+            self.emit(event);
+            replayed += 1;
+        }
+
+        replayed
+    }
+
+    fn apply_event(state: &mut HeadlessState, event: &WindowEvent) {
+        match event {
+            WindowEvent::WindowCreated(window) => {
+                state.windows.retain(|w| w.id != window.id);
+                state.windows.push(window.clone());
+            }
+            WindowEvent::WindowDestroyed(id) => {
+                state.windows.retain(|w| w.id != *id);
+            }
+            WindowEvent::WindowMoved(id, rect) | WindowEvent::WindowResized(id, rect) => {
+                if let Some(window) = state.windows.iter_mut().find(|w| w.id == *id) {
+                    window.rect = *rect;
+                }
+            }
+            WindowEvent::WindowFocused(id) => {
+                for window in &mut state.windows {
+                    window.is_focused = window.id == *id;
+                }
+            }
+            WindowEvent::WindowMinimized(id) | WindowEvent::WindowUnminimized(id) => {
+                let minimized = matches!(event, WindowEvent::WindowMinimized(_));
+                if let Some(window) = state.windows.iter_mut().find(|w| w.id == *id) {
+                    window.is_minimized = minimized;
+                }
+            }
+            WindowEvent::WorkspaceChanged(_)
+            | WindowEvent::MouseMoved { .. }
+            | WindowEvent::MouseReleased { .. } => {}
+        }
+    }
+
+    fn emit(&self, event: WindowEvent) {
+        if let Err(e) = self.event_sender.try_send(event) {
+            debug!("Dropping headless event: {}", e);
+        }
+    }
+}
+
+impl WindowBackend for HeadlessBackend {
+    fn start_monitoring(&mut self) -> Result<()> {
+        debug!("Starting headless window monitoring");
+        self.replay_events();
+        Ok(())
+    }
+
+    fn get_windows(&self) -> Result<Vec<Window>> {
+        Ok(self.windows())
+    }
+
+    fn get_displays(&self) -> &HashMap<u32, Display> {
+        &self.displays
+    }
+
+    fn refresh_displays(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn focus_window(&mut self, window_id: WindowId) -> Result<()> {
+        let found = {
+            let mut state = self.state.lock().unwrap();
+            let found = state.windows.iter().any(|w| w.id == window_id);
+            if found {
+                for window in &mut state.windows {
+                    window.is_focused = window.id == window_id;
+                }
+            }
+            found
+        };
+
+        if found {
+            self.emit(WindowEvent::WindowFocused(window_id));
+        }
+        Ok(())
+    }
+
+    fn move_window(&mut self, window_id: WindowId, rect: Rect) -> Result<()> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(window) = state.windows.iter_mut().find(|w| w.id == window_id) {
+            // Apps clamp requested sizes to their own limits
+            window.rect = window.size_hints.constrain(rect);
+        }
+        Ok(())
+    }
+
+    fn close_window(&mut self, window_id: WindowId) -> Result<()> {
+        let found = {
+            let mut state = self.state.lock().unwrap();
+            let before = state.windows.len();
+            state.windows.retain(|w| w.id != window_id);
+            state.windows.len() != before
+        };
+
+        if found {
+            self.emit(WindowEvent::WindowDestroyed(window_id));
+        }
+        Ok(())
+    }
+
+    fn get_focused_window(&self) -> Result<Option<WindowId>> {
+        let state = self.state.lock().unwrap();
+        Ok(state.windows.iter().find(|w| w.is_focused).map(|w| w.id))
+    }
+}
@@ -0,0 +1,113 @@
+pub mod headless;
+
+pub use headless::HeadlessBackend;
+
+use crate::{Rect, Result, Window, WindowId};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct Display {
+    pub id: u32,
+    pub rect: Rect,
+    pub is_main: bool,
+    pub name: String,
+}
+
+/// Everything the window manager needs from the underlying window system.
+///
+/// `MacOSWindowSystem` talks to the Accessibility and Core Graphics APIs,
+/// `HeadlessBackend` keeps windows and displays in memory so the event loop,
+/// layouts and command handling can run anywhere.
+pub trait WindowBackend {
+    fn start_monitoring(&mut self) -> Result<()>;
+    fn get_windows(&self) -> Result<Vec<Window>>;
+    fn get_displays(&self) -> &HashMap<u32, Display>;
+    fn refresh_displays(&mut self) -> Result<()>;
+    fn focus_window(&mut self, window_id: WindowId) -> Result<()>;
+    fn move_window(&mut self, window_id: WindowId, rect: Rect) -> Result<()>;
+    fn close_window(&mut self, window_id: WindowId) -> Result<()>;
+    fn get_focused_window(&self) -> Result<Option<WindowId>>;
+
+    fn get_screen_rect(&self) -> Result<Rect> {
+        // Return main display rect for backward compatibility
+        self.get_main_display_rect()
+    }
+
+    fn get_main_display_rect(&self) -> Result<Rect> {
+        let main_display = self
+            .get_displays()
+            .values()
+            .find(|d| d.is_main)
+            .ok_or_else(|| anyhow::anyhow!("No main display found"))?;
+        Ok(main_display.rect)
+    }
+
+    fn get_display_for_window(&self, window: &Window) -> Option<&Display> {
+        // Find which display contains the center of the window
+        let window_center_x = window.rect.x + window.rect.width / 2.0;
+        let window_center_y = window.rect.y + window.rect.height / 2.0;
+
+        self.get_displays().values().find(|display| {
+            window_center_x >= display.rect.x
+                && window_center_x < display.rect.x + display.rect.width
+                && window_center_y >= display.rect.y
+                && window_center_y < display.rect.y + display.rect.height
+        })
+    }
+
+    fn get_display_by_id(&self, display_id: u32) -> Option<&Display> {
+        self.get_displays().get(&display_id)
+    }
+
+    fn get_windows_by_display<'a>(&self, windows: &'a [Window]) -> HashMap<u32, Vec<&'a Window>> {
+        let mut windows_by_display: HashMap<u32, Vec<&'a Window>> = HashMap::new();
+
+        // Initialize empty vectors for each display
+        for display_id in self.get_displays().keys() {
+            windows_by_display.insert(*display_id, Vec::new());
+        }
+
+        // Assign windows to displays
+        for window in windows {
+            let display_id = match self.get_display_for_window(window) {
+                Some(display) => Some(display.id),
+                // If window doesn't clearly belong to any display, assign to main display
+                None => self
+                    .get_displays()
+                    .values()
+                    .find(|d| d.is_main)
+                    .map(|d| d.id),
+            };
+
+            if let Some(display_id) = display_id {
+                windows_by_display
+                    .entry(display_id)
+                    .or_default()
+                    .push(window);
+            }
+        }
+
+        windows_by_display
+    }
+
+    fn move_window_to_display(
+        &mut self,
+        window_id: WindowId,
+        target_display_id: u32,
+    ) -> Result<()> {
+        let target_rect = self
+            .get_displays()
+            .get(&target_display_id)
+            .map(|display| display.rect)
+            .ok_or_else(|| anyhow::anyhow!("Display {} not found", target_display_id))?;
+
+        // Calculate new position centered on the target display
+        let new_rect = Rect::new(
+            target_rect.x + target_rect.width * 0.1,
+            target_rect.y + target_rect.height * 0.1,
+            target_rect.width * 0.8,
+            target_rect.height * 0.8,
+        );
+        self.move_window(window_id, new_rect)
+    }
+}
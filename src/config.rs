@@ -1,5 +1,6 @@
-use crate::Result;
+use crate::{Rect, Result};
 use serde::{Deserialize, Serialize};
+use std::ops::Range;
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -15,7 +16,13 @@ pub struct Config {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeneralConfig {
     #[serde(default = "default_gap")]
-    pub gap: f64,
+    pub inner_gap: f64,
+    #[serde(default = "default_gap")]
+    pub outer_gap: f64,
+    #[serde(default)]
+    pub padding: PaddingConfig,
+    #[serde(default)]
+    pub smart_gaps: bool,
     #[serde(default = "default_border_width")]
     pub border_width: f64,
     #[serde(default = "default_border_color")]
@@ -24,12 +31,104 @@ pub struct GeneralConfig {
     pub active_border_color: String,
 }
 
+/// Space kept clear along each screen edge, e.g. for a status bar.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PaddingConfig {
+    #[serde(default)]
+    pub top: f64,
+    #[serde(default)]
+    pub bottom: f64,
+    #[serde(default)]
+    pub left: f64,
+    #[serde(default)]
+    pub right: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LayoutConfig {
     #[serde(default = "default_layout_type")]
     pub default_layout: String,
+    /// Layouts `toggle_layout`, `layout_next` and `layout_prev` step
+    /// through, in order. Empty means every registered layout.
+    #[serde(default)]
+    pub cycle: Vec<String>,
     #[serde(default = "default_split_ratio")]
     pub split_ratio: f64,
+    #[serde(default = "default_split_mode")]
+    pub split_mode: String,
+    #[serde(default = "default_nmaster")]
+    pub nmaster: usize,
+    #[serde(default = "default_master_position")]
+    pub master_position: String,
+    #[serde(default = "default_three_column_ratios")]
+    pub three_column_ratios: Vec<f64>,
+    #[serde(default = "default_column_width")]
+    pub column_width: f64,
+    #[serde(default = "default_title_bar_height")]
+    pub title_bar_height: f64,
+    #[serde(default)]
+    pub grid_rows: usize,
+    #[serde(default)]
+    pub grid_columns: usize,
+    #[serde(default = "default_grid_order")]
+    pub grid_order: String,
+    #[serde(default)]
+    pub grid_stretch_last: bool,
+    #[serde(default = "default_history_size")]
+    pub history_size: usize,
+    #[serde(default = "default_preset_dir")]
+    pub preset_dir: String,
+    #[serde(default)]
+    pub rules: Vec<LayoutRule>,
+    #[serde(default)]
+    pub templates: Vec<LayoutTemplate>,
+}
+
+/// Chooses a layout automatically. Rules are tried in order and the first
+/// whose conditions all hold wins; a condition left out always holds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutRule {
+    pub layout: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_windows: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_windows: Option<usize>,
+    /// Display width divided by height, e.g. 2.3 for a 21:9 ultrawide.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_aspect_ratio: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_aspect_ratio: Option<f64>,
+    /// Workspaces the rule applies to; empty means all of them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workspaces: Vec<u32>,
+}
+
+/// A layout described as a grid of named areas, in the manner of CSS
+/// `grid-template-areas`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutTemplate {
+    pub name: String,
+    /// Relative heights of the rows.
+    pub rows: Vec<f64>,
+    /// Relative widths of the columns.
+    pub columns: Vec<f64>,
+    /// One string per row with the area name of each column, separated by
+    /// spaces. `.` leaves a cell empty.
+    pub areas: Vec<String>,
+    /// Area names in the order windows fill them.
+    pub slots: Vec<String>,
+    /// Where windows beyond the last slot go: `stack` shares the last slot
+    /// between them, `monocle` piles them on top of it and `float` leaves
+    /// them where they are.
+    #[serde(default = "default_template_overflow")]
+    pub overflow: String,
+}
+
+/// The cells a template area covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateArea {
+    pub rows: Range<usize>,
+    pub columns: Range<usize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -60,6 +159,9 @@ pub struct PluginConfig {
     pub plugin_dir: String,
 }
 
+/// Largest inner or outer gap accepted, in pixels.
+pub const MAX_GAP: f64 = 100.0;
+
 fn default_gap() -> f64 {
     10.0
 }
@@ -78,6 +180,45 @@ fn default_layout_type() -> String {
 fn default_split_ratio() -> f64 {
     0.5
 }
+fn default_split_mode() -> String {
+    "longest_side".to_string()
+}
+
+fn default_nmaster() -> usize {
+    1
+}
+
+fn default_master_position() -> String {
+    "left".to_string()
+}
+
+fn default_three_column_ratios() -> Vec<f64> {
+    vec![1.0, 1.0, 1.0]
+}
+
+fn default_column_width() -> f64 {
+    0.5
+}
+
+fn default_title_bar_height() -> f64 {
+    24.0
+}
+
+fn default_grid_order() -> String {
+    "row_major".to_string()
+}
+fn default_history_size() -> usize {
+    50
+}
+fn default_template_overflow() -> String {
+    "stack".to_string()
+}
+fn default_preset_dir() -> String {
+    format!(
+        "{}/.config/skew/presets",
+        std::env::var("HOME").unwrap_or_else(|_| ".".to_string())
+    )
+}
 fn default_focus_follows_mouse() -> bool {
     true
 }
@@ -98,14 +239,32 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             general: GeneralConfig {
-                gap: default_gap(),
+                inner_gap: default_gap(),
+                outer_gap: default_gap(),
+                padding: PaddingConfig::default(),
+                smart_gaps: false,
                 border_width: default_border_width(),
                 border_color: default_border_color(),
                 active_border_color: default_active_border_color(),
             },
             layout: LayoutConfig {
                 default_layout: default_layout_type(),
+                cycle: vec![],
                 split_ratio: default_split_ratio(),
+                split_mode: default_split_mode(),
+                nmaster: default_nmaster(),
+                master_position: default_master_position(),
+                three_column_ratios: default_three_column_ratios(),
+                column_width: default_column_width(),
+                title_bar_height: default_title_bar_height(),
+                grid_rows: 0,
+                grid_columns: 0,
+                grid_order: default_grid_order(),
+                grid_stretch_last: false,
+                history_size: default_history_size(),
+                preset_dir: default_preset_dir(),
+                rules: vec![],
+                templates: vec![],
             },
             focus: FocusConfig {
                 follows_mouse: default_focus_follows_mouse(),
@@ -170,7 +329,7 @@ impl Config {
         }
 
         let content = std::fs::read_to_string(path)?;
-        let config: Self = toml::from_str(&content)?;
+        let config = Self::from_toml(&content)?;
 
         // Validate the loaded configuration
         config.validate().map_err(|e| {
@@ -185,6 +344,23 @@ impl Config {
         Ok(config)
     }
 
+    /// Parse a config file's contents without validating them.
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let mut table: toml::Table = content.parse()?;
+
+        // Older configs had one `gap` for both the space between windows
+        // and the space along the screen edges
+        if let Some(toml::Value::Table(general)) = table.get_mut("general") {
+            if let Some(gap) = general.remove("gap") {
+                for key in ["inner_gap", "outer_gap"] {
+                    general.entry(key).or_insert_with(|| gap.clone());
+                }
+            }
+        }
+
+        Ok(toml::Value::Table(table).try_into()?)
+    }
+
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         let path = path.as_ref();
 
@@ -215,11 +391,30 @@ impl Config {
 
 impl GeneralConfig {
     pub fn validate(&self) -> Result<()> {
-        if self.gap < 0.0 || self.gap > 100.0 {
-            return Err(anyhow::anyhow!(
-                "gap must be between 0 and 100, got {}",
-                self.gap
-            ));
+        for (name, gap) in [("inner_gap", self.inner_gap), ("outer_gap", self.outer_gap)] {
+            if !(0.0..=MAX_GAP).contains(&gap) {
+                return Err(anyhow::anyhow!(
+                    "{} must be between 0 and {}, got {}",
+                    name,
+                    MAX_GAP,
+                    gap
+                ));
+            }
+        }
+
+        for (side, padding) in [
+            ("top", self.padding.top),
+            ("bottom", self.padding.bottom),
+            ("left", self.padding.left),
+            ("right", self.padding.right),
+        ] {
+            if !padding.is_finite() || padding < 0.0 {
+                return Err(anyhow::anyhow!(
+                    "padding.{} must be a non-negative number, got {}",
+                    side,
+                    padding
+                ));
+            }
         }
 
         if self.border_width < 0.0 || self.border_width > 20.0 {
@@ -249,15 +444,25 @@ impl GeneralConfig {
 
 impl LayoutConfig {
     pub fn validate(&self) -> Result<()> {
-        let valid_layouts = [
-            "bsp", "stack", "float", "grid", "spiral", "column", "monocle",
-        ];
-        if !valid_layouts.contains(&self.default_layout.to_lowercase().as_str()) {
-            return Err(anyhow::anyhow!(
-                "default_layout must be one of {:?}, got '{}'",
-                valid_layouts,
-                self.default_layout
-            ));
+        // Layout names are checked by LayoutManager::validate_config, once
+        // plugins have registered their layouts
+        if self.default_layout.is_empty() {
+            return Err(anyhow::anyhow!("default_layout must not be empty"));
+        }
+
+        for (i, name) in self.cycle.iter().enumerate() {
+            if name.is_empty() {
+                return Err(anyhow::anyhow!("cycle must not contain empty layout names"));
+            }
+            if self.cycle[..i]
+                .iter()
+                .any(|other| other.eq_ignore_ascii_case(name))
+            {
+                return Err(anyhow::anyhow!(
+                    "cycle lists layout '{}' more than once",
+                    name
+                ));
+            }
         }
 
         if self.split_ratio <= 0.0 || self.split_ratio >= 1.0 {
@@ -267,10 +472,264 @@ impl LayoutConfig {
             ));
         }
 
+        let valid_split_modes = [
+            "longest_side",
+            "alternate",
+            "fixed_horizontal",
+            "fixed_vertical",
+        ];
+        if !valid_split_modes.contains(&self.split_mode.to_lowercase().as_str()) {
+            return Err(anyhow::anyhow!(
+                "split_mode must be one of {:?}, got '{}'",
+                valid_split_modes,
+                self.split_mode
+            ));
+        }
+
+        let valid_master_positions = ["left", "right", "top", "bottom"];
+        if !valid_master_positions.contains(&self.master_position.to_lowercase().as_str()) {
+            return Err(anyhow::anyhow!(
+                "master_position must be one of {:?}, got '{}'",
+                valid_master_positions,
+                self.master_position
+            ));
+        }
+
+        if self.three_column_ratios.len() != 3
+            || self
+                .three_column_ratios
+                .iter()
+                .any(|ratio| !ratio.is_finite() || *ratio <= 0.0)
+        {
+            return Err(anyhow::anyhow!(
+                "three_column_ratios must be three positive numbers, got {:?}",
+                self.three_column_ratios
+            ));
+        }
+
+        if !(0.1..=1.0).contains(&self.column_width) {
+            return Err(anyhow::anyhow!(
+                "column_width must be between 0.1 and 1, got {}",
+                self.column_width
+            ));
+        }
+
+        if !self.title_bar_height.is_finite() || self.title_bar_height < 0.0 {
+            return Err(anyhow::anyhow!(
+                "title_bar_height must be a non-negative number, got {}",
+                self.title_bar_height
+            ));
+        }
+
+        if self.grid_rows > 0 && self.grid_columns > 0 {
+            return Err(anyhow::anyhow!(
+                "grid_rows and grid_columns cannot both be fixed, got {} and {}",
+                self.grid_rows,
+                self.grid_columns
+            ));
+        }
+
+        let valid_grid_orders = ["row_major", "column_major"];
+        if !valid_grid_orders.contains(&self.grid_order.to_lowercase().as_str()) {
+            return Err(anyhow::anyhow!(
+                "grid_order must be one of {:?}, got '{}'",
+                valid_grid_orders,
+                self.grid_order
+            ));
+        }
+
+        if self.preset_dir.is_empty() {
+            return Err(anyhow::anyhow!("preset_dir cannot be empty"));
+        }
+
+        for (i, rule) in self.rules.iter().enumerate() {
+            rule.validate()
+                .map_err(|e| anyhow::anyhow!("rule {}: {}", i + 1, e))?;
+        }
+
+        for (i, template) in self.templates.iter().enumerate() {
+            template
+                .validate()
+                .map_err(|e| anyhow::anyhow!("template '{}': {}", template.name, e))?;
+            if crate::layout::is_builtin_name(&template.name) {
+                return Err(anyhow::anyhow!(
+                    "template '{}' is named like a built-in layout",
+                    template.name
+                ));
+            }
+            if self.templates[..i]
+                .iter()
+                .any(|other| other.name.eq_ignore_ascii_case(&template.name))
+            {
+                return Err(anyhow::anyhow!(
+                    "template '{}' is defined more than once",
+                    template.name
+                ));
+            }
+        }
+
         Ok(())
     }
 }
 
+impl LayoutRule {
+    pub fn validate(&self) -> Result<()> {
+        // The layout name is checked by LayoutManager::validate_config
+        if self.layout.is_empty() {
+            return Err(anyhow::anyhow!("layout must not be empty"));
+        }
+
+        if let (Some(min), Some(max)) = (self.min_windows, self.max_windows) {
+            if min > max {
+                return Err(anyhow::anyhow!(
+                    "min_windows ({}) is larger than max_windows ({})",
+                    min,
+                    max
+                ));
+            }
+        }
+
+        for (name, ratio) in [
+            ("min_aspect_ratio", self.min_aspect_ratio),
+            ("max_aspect_ratio", self.max_aspect_ratio),
+        ] {
+            if let Some(ratio) = ratio {
+                if !ratio.is_finite() || ratio <= 0.0 {
+                    return Err(anyhow::anyhow!("{} must be positive, got {}", name, ratio));
+                }
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_aspect_ratio, self.max_aspect_ratio) {
+            if min > max {
+                return Err(anyhow::anyhow!(
+                    "min_aspect_ratio ({}) is larger than max_aspect_ratio ({})",
+                    min,
+                    max
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether the rule applies to `workspace` showing `window_count`
+    /// windows on a display of the given size.
+    pub fn matches(&self, workspace: u32, window_count: usize, screen_rect: &Rect) -> bool {
+        let aspect_ratio = screen_rect.width / screen_rect.height;
+        self.min_windows.is_none_or(|min| window_count >= min)
+            && self.max_windows.is_none_or(|max| window_count <= max)
+            && self.min_aspect_ratio.is_none_or(|min| aspect_ratio >= min)
+            && self.max_aspect_ratio.is_none_or(|max| aspect_ratio <= max)
+            && (self.workspaces.is_empty() || self.workspaces.contains(&workspace))
+    }
+}
+
+impl LayoutTemplate {
+    pub fn validate(&self) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(anyhow::anyhow!("name must not be empty"));
+        }
+
+        for (name, sizes) in [("rows", &self.rows), ("columns", &self.columns)] {
+            if sizes.is_empty() || sizes.iter().any(|size| !size.is_finite() || *size <= 0.0) {
+                return Err(anyhow::anyhow!(
+                    "{} must be a list of positive fractions, got {:?}",
+                    name,
+                    sizes
+                ));
+            }
+        }
+
+        let valid_overflows = ["stack", "monocle", "float"];
+        if !valid_overflows.contains(&self.overflow.to_lowercase().as_str()) {
+            return Err(anyhow::anyhow!(
+                "overflow must be one of {:?}, got '{}'",
+                valid_overflows,
+                self.overflow
+            ));
+        }
+
+        self.slot_areas()?;
+        Ok(())
+    }
+
+    /// The cells of each slot's area, in slot order. Every named area must
+    /// be a rectangle and appear in `slots` exactly once.
+    pub fn slot_areas(&self) -> Result<Vec<TemplateArea>> {
+        if self.slots.is_empty() {
+            return Err(anyhow::anyhow!("slots must name at least one area"));
+        }
+        if self.areas.len() != self.rows.len() {
+            return Err(anyhow::anyhow!(
+                "areas has {} rows but rows has {} sizes",
+                self.areas.len(),
+                self.rows.len()
+            ));
+        }
+
+        // Bounding box and cell count of each area, in order of appearance
+        let mut areas: Vec<(&str, TemplateArea, usize)> = Vec::new();
+        for (row, line) in self.areas.iter().enumerate() {
+            let cells: Vec<&str> = line.split_whitespace().collect();
+            if cells.len() != self.columns.len() {
+                return Err(anyhow::anyhow!(
+                    "areas row {} has {} cells but columns has {} sizes",
+                    row + 1,
+                    cells.len(),
+                    self.columns.len()
+                ));
+            }
+
+            for (column, name) in cells.into_iter().enumerate() {
+                if name == "." {
+                    continue;
+                }
+                match areas
+                    .iter_mut()
+                    .find(|(area_name, _, _)| *area_name == name)
+                {
+                    Some((_, area, count)) => {
+                        area.rows.start = area.rows.start.min(row);
+                        area.rows.end = area.rows.end.max(row + 1);
+                        area.columns.start = area.columns.start.min(column);
+                        area.columns.end = area.columns.end.max(column + 1);
+                        *count += 1;
+                    }
+                    None => areas.push((
+                        name,
+                        TemplateArea {
+                            rows: row..row + 1,
+                            columns: column..column + 1,
+                        },
+                        1,
+                    )),
+                }
+            }
+        }
+
+        for (name, area, count) in &areas {
+            if area.rows.len() * area.columns.len() != *count {
+                return Err(anyhow::anyhow!("area '{}' is not a rectangle", name));
+            }
+            if !self.slots.iter().any(|slot| slot == name) {
+                return Err(anyhow::anyhow!("area '{}' is missing from slots", name));
+            }
+        }
+
+        let mut slot_areas = Vec::new();
+        for (i, slot) in self.slots.iter().enumerate() {
+            if self.slots[..i].contains(slot) {
+                return Err(anyhow::anyhow!("slot '{}' is listed more than once", slot));
+            }
+            match areas.iter().find(|(name, _, _)| name == slot) {
+                Some((_, area, _)) => slot_areas.push(area.clone()),
+                None => return Err(anyhow::anyhow!("slot '{}' is not an area", slot)),
+            }
+        }
+        Ok(slot_areas)
+    }
+}
+
 impl FocusConfig {
     pub fn validate(&self) -> Result<()> {
         if self.mouse_delay_ms > 10000 {
@@ -344,10 +803,50 @@ impl HotkeyConfig {
                 "move_down",
                 "close_window",
                 "toggle_layout",
+                "layout",
+                "layout_next",
+                "layout_prev",
                 "toggle_float",
                 "toggle_fullscreen",
                 "swap_main",
+                "move_first",
+                "move_last",
                 "restart",
+                "presel_left",
+                "presel_right",
+                "presel_up",
+                "presel_down",
+                "presel_cancel",
+                "toggle_split",
+                "rotate",
+                "mirror",
+                "balance",
+                "resize_left",
+                "resize_right",
+                "resize_up",
+                "resize_down",
+                "inc_master",
+                "dec_master",
+                "master_position",
+                "inc_weight",
+                "dec_weight",
+                "reset_weight",
+                "column_wider",
+                "column_narrower",
+                "move_column_left",
+                "move_column_right",
+                "container",
+                "tab_next",
+                "tab_prev",
+                "cycle_next",
+                "cycle_prev",
+                "gap_inc",
+                "gap_dec",
+                "gap_set",
+                "layout_command",
+                "layout_undo",
+                "layout_auto",
+                "layout_redo",
                 "exec",
             ];
 
@@ -360,10 +859,23 @@ impl HotkeyConfig {
                 ));
             }
 
-            // Special validation for exec actions
-            if action_name == "exec" && action_parts.len() < 2 {
+            // Special validation for actions that take an argument
+            if [
+                "exec",
+                "rotate",
+                "mirror",
+                "master_position",
+                "container",
+                "gap_set",
+                "layout",
+                "layout_command",
+            ]
+            .contains(&action_name)
+                && action_parts.len() < 2
+            {
                 return Err(anyhow::anyhow!(
-                    "exec action requires an argument: '{}'",
+                    "{} action requires an argument: '{}'",
+                    action_name,
                     action
                 ));
             }
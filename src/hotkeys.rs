@@ -1,4 +1,8 @@
 use crate::config::HotkeyConfig;
+use crate::layout::{
+    ContainerStyle, MasterPosition, MirrorAxis, Rotation, DEFAULT_COLUMN_WIDTH_STEP,
+    DEFAULT_GAP_STEP, DEFAULT_RESIZE_STEP, DEFAULT_WEIGHT_STEP,
+};
 use crate::window_manager::Command;
 use crate::Result;
 use log::{debug, error, info, warn};
@@ -11,7 +15,7 @@ use tokio::sync::mpsc;
 // Global state for rdev callback - necessary because rdev requires function pointers
 static GLOBAL_HOTKEY_SENDER: OnceLock<std::sync::mpsc::Sender<rdev::Event>> = OnceLock::new();
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
     Left,
     Right,
@@ -19,6 +23,18 @@ pub enum Direction {
     Down,
 }
 
+impl Direction {
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "left" | "west" => Some(Self::Left),
+            "right" | "east" => Some(Self::Right),
+            "up" | "north" => Some(Self::Up),
+            "down" | "south" => Some(Self::Down),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct KeyCombination {
     pub modifiers: Vec<ModifierKey>,
@@ -404,6 +420,19 @@ impl HotkeyManager {
         })
     }
 
+    fn parse_ratio(value: &str) -> Result<f64> {
+        let ratio = value
+            .parse::<f64>()
+            .map_err(|_| anyhow::anyhow!("Invalid ratio: {}", value))?;
+        if !(ratio > 0.0 && ratio < 1.0) {
+            return Err(anyhow::anyhow!(
+                "Ratio must be between 0 and 1, got {}",
+                ratio
+            ));
+        }
+        Ok(ratio)
+    }
+
     fn parse_action(action: &str) -> Result<Command> {
         
         let parts: Vec<&str> = action.split(':').collect();
@@ -419,11 +448,137 @@ impl HotkeyManager {
             "move_up" => Ok(Command::MoveDirection(Direction::Up)),
             "move_down" => Ok(Command::MoveDirection(Direction::Down)),
             "close_window" => Ok(Command::CloseFocusedWindow),
-            "toggle_layout" => Ok(Command::ToggleLayout),
+            "toggle_layout" | "layout_next" => Ok(Command::ToggleLayout),
+            "layout_prev" => Ok(Command::PreviousLayout),
+            "layout" => match parts.get(1) {
+                Some(name) => Ok(Command::SetLayout(name.to_string(), None)),
+                None => Err(anyhow::anyhow!("layout requires a layout name: {}", action)),
+            },
             "toggle_float" => Ok(Command::ToggleFloat),
             "toggle_fullscreen" => Ok(Command::ToggleFullscreen),
             "swap_main" => Ok(Command::SwapMain),
+            "move_first" => Ok(Command::MoveToFirst),
+            "move_last" => Ok(Command::MoveToLast),
             "restart" => Ok(Command::ReloadConfig),
+            "presel_left" | "presel_right" | "presel_up" | "presel_down" => {
+                let direction = Direction::from_name(&command["presel_".len()..])
+                    .ok_or_else(|| anyhow::anyhow!("Unknown action: {}", action))?;
+                let ratio = match parts.get(1) {
+                    Some(ratio) => Some(Self::parse_ratio(ratio)?),
+                    None => None,
+                };
+                Ok(Command::Preselect(direction, ratio))
+            }
+            "presel_cancel" => Ok(Command::CancelPreselect),
+            "toggle_split" => Ok(Command::ToggleSplit),
+            "rotate" => parts
+                .get(1)
+                .and_then(|degrees| Rotation::from_degrees(degrees))
+                .map(Command::Rotate)
+                .ok_or_else(|| anyhow::anyhow!("rotate requires 90, 180 or 270: {}", action)),
+            "mirror" => parts
+                .get(1)
+                .and_then(|axis| MirrorAxis::from_name(axis))
+                .map(Command::Mirror)
+                .ok_or_else(|| anyhow::anyhow!("mirror requires x or y: {}", action)),
+            "balance" => Ok(Command::Balance),
+            "resize_left" | "resize_right" | "resize_up" | "resize_down" => {
+                let direction = Direction::from_name(&command["resize_".len()..])
+                    .ok_or_else(|| anyhow::anyhow!("Unknown action: {}", action))?;
+                let pixels = match parts.get(1) {
+                    Some(pixels) => match pixels.parse::<f64>() {
+                        Ok(amount) if amount.is_finite() && amount > 0.0 => amount,
+                        _ => return Err(anyhow::anyhow!("Invalid resize amount: {}", pixels)),
+                    },
+                    None => DEFAULT_RESIZE_STEP,
+                };
+                Ok(Command::Resize(direction, pixels))
+            }
+            "inc_master" => Ok(Command::IncMaster),
+            "dec_master" => Ok(Command::DecMaster),
+            "master_position" => parts
+                .get(1)
+                .and_then(|position| MasterPosition::from_name(position))
+                .map(Command::SetMasterPosition)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "master_position requires left, right, top or bottom: {}",
+                        action
+                    )
+                }),
+            "inc_weight" | "dec_weight" => {
+                let step = match parts.get(1) {
+                    Some(step) => match step.parse::<f64>() {
+                        Ok(step) if step.is_finite() => step,
+                        _ => return Err(anyhow::anyhow!("Invalid weight step: {}", step)),
+                    },
+                    None => DEFAULT_WEIGHT_STEP,
+                };
+                Ok(Command::AdjustWeight(if command == "inc_weight" {
+                    step
+                } else {
+                    -step
+                }))
+            }
+            "reset_weight" => Ok(Command::ResetWeight),
+            "column_wider" | "column_narrower" => {
+                let step = match parts.get(1) {
+                    Some(step) => match step.parse::<f64>() {
+                        Ok(step) if step.is_finite() => step,
+                        _ => return Err(anyhow::anyhow!("Invalid column width step: {}", step)),
+                    },
+                    None => DEFAULT_COLUMN_WIDTH_STEP,
+                };
+                Ok(Command::AdjustColumnWidth(if command == "column_wider" {
+                    step
+                } else {
+                    -step
+                }))
+            }
+            "move_column_left" => Ok(Command::MoveColumn(Direction::Left)),
+            "move_column_right" => Ok(Command::MoveColumn(Direction::Right)),
+            "container" => match parts.get(1) {
+                Some(&"split") => Ok(Command::SetContainerStyle(None)),
+                style => style
+                    .and_then(|style| ContainerStyle::from_name(style))
+                    .map(|style| Command::SetContainerStyle(Some(style)))
+                    .ok_or_else(|| {
+                        anyhow::anyhow!("container requires tabbed, stacked or split: {}", action)
+                    }),
+            },
+            "tab_next" => Ok(Command::CycleContainer(true)),
+            "tab_prev" => Ok(Command::CycleContainer(false)),
+            "cycle_next" => Ok(Command::CycleWindow(true)),
+            "cycle_prev" => Ok(Command::CycleWindow(false)),
+            "gap_inc" | "gap_dec" => {
+                let step = match parts.get(1) {
+                    Some(step) => match step.parse::<f64>() {
+                        Ok(step) if step.is_finite() => step,
+                        _ => return Err(anyhow::anyhow!("Invalid gap step: {}", step)),
+                    },
+                    None => DEFAULT_GAP_STEP,
+                };
+                Ok(Command::AdjustGap(if command == "gap_inc" { step } else { -step }))
+            }
+            "layout_auto" => Ok(Command::EnableLayoutRules),
+            "layout_undo" => Ok(Command::LayoutUndo),
+            "layout_redo" => Ok(Command::LayoutRedo),
+            "layout_command" => match parts.get(1) {
+                Some(name) => Ok(Command::LayoutCommand(
+                    name.to_string(),
+                    parts[2..].iter().map(|arg| arg.to_string()).collect(),
+                )),
+                None => Err(anyhow::anyhow!(
+                    "layout_command requires a command: {}",
+                    action
+                )),
+            },
+            "gap_set" => parts
+                .get(1)
+                .and_then(|gap| gap.parse::<f64>().ok())
+                .filter(|gap| gap.is_finite())
+                .map(Command::SetGap)
+                .ok_or_else(|| anyhow::anyhow!("gap_set requires a size in pixels: {}", action)),
             "exec" => {
                 if parts.len() > 1 {
                     info!("Application launch requested: {}", parts[1]);
@@ -445,7 +600,7 @@ impl HotkeyManager {
                                 .map_err(|e| anyhow::anyhow!("Failed to launch {}: {}", app, e))?;
                         }
                     }
-                    Ok(Command::GetStatus)
+                    Ok(Command::GetStatus(None))
                 } else {
                     Err(anyhow::anyhow!("exec command requires an argument"))
                 }
@@ -219,10 +219,279 @@ impl IpcServer {
                     };
                 }
             }
-            "toggle-layout" => Command::ToggleLayout,
+            "swap" => {
+                let ids = (
+                    message.args.first().map(|id| id.parse::<u32>()),
+                    message.args.get(1).map(|id| id.parse::<u32>()),
+                );
+                match ids {
+                    (Some(Ok(a)), Some(Ok(b))) => Command::SwapWindows(WindowId(a), WindowId(b)),
+                    _ => {
+                        return IpcResponse {
+                            success: false,
+                            message: "swap command requires two window IDs".to_string(),
+                            data: None,
+                        };
+                    }
+                }
+            }
+            "swap-main" => Command::SwapMain,
+            "move-first" => Command::MoveToFirst,
+            "move-last" => Command::MoveToLast,
+            "presel" => {
+                let direction = message.args.first().map(|s| s.as_str());
+                let ratio = message.args.get(1).map(|r| r.parse::<f64>());
+
+                match (direction, ratio) {
+                    (Some("cancel"), None) => Command::CancelPreselect,
+                    (Some(name), ratio) => {
+                        match (crate::hotkeys::Direction::from_name(name), ratio) {
+                            (Some(direction), None) => Command::Preselect(direction, None),
+                            (Some(direction), Some(Ok(ratio))) if ratio > 0.0 && ratio < 1.0 => {
+                                Command::Preselect(direction, Some(ratio))
+                            }
+                            _ => return Self::presel_usage(),
+                        }
+                    }
+                    _ => return Self::presel_usage(),
+                }
+            }
+            "toggle-layout" | "layout-next" => Command::ToggleLayout,
+            "layout-prev" => Command::PreviousLayout,
+            "layout" => match message.args.first() {
+                Some(name) => {
+                    return Self::query(command_sender, |reply| {
+                        Command::SetLayout(name.clone(), Some(reply))
+                    })
+                    .await;
+                }
+                None => {
+                    return IpcResponse {
+                        success: false,
+                        message: "layout command requires: <name>".to_string(),
+                        data: None,
+                    };
+                }
+            },
+            "toggle-split" => Command::ToggleSplit,
+            "rotate" => {
+                match message
+                    .args
+                    .first()
+                    .and_then(|degrees| crate::layout::Rotation::from_degrees(degrees))
+                {
+                    Some(rotation) => Command::Rotate(rotation),
+                    None => {
+                        return IpcResponse {
+                            success: false,
+                            message: "rotate command requires: 90|180|270".to_string(),
+                            data: None,
+                        };
+                    }
+                }
+            }
+            "mirror" => {
+                match message
+                    .args
+                    .first()
+                    .and_then(|axis| crate::layout::MirrorAxis::from_name(axis))
+                {
+                    Some(axis) => Command::Mirror(axis),
+                    None => {
+                        return IpcResponse {
+                            success: false,
+                            message: "mirror command requires: x|y".to_string(),
+                            data: None,
+                        };
+                    }
+                }
+            }
+            "balance" => Command::Balance,
+            "resize" => {
+                let direction = message
+                    .args
+                    .first()
+                    .and_then(|name| crate::hotkeys::Direction::from_name(name));
+                let pixels = match message.args.get(1) {
+                    Some(pixels) => pixels
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|pixels| pixels.is_finite() && *pixels > 0.0),
+                    None => Some(crate::layout::DEFAULT_RESIZE_STEP),
+                };
+
+                match (direction, pixels) {
+                    (Some(direction), Some(pixels)) => Command::Resize(direction, pixels),
+                    _ => {
+                        return IpcResponse {
+                            success: false,
+                            message: "resize command requires: left|right|up|down [pixels]"
+                                .to_string(),
+                            data: None,
+                        };
+                    }
+                }
+            }
+            "master" => match message.args.first().map(|arg| arg.as_str()) {
+                Some("inc") => Command::IncMaster,
+                Some("dec") => Command::DecMaster,
+                Some("position") => {
+                    match message
+                        .args
+                        .get(1)
+                        .and_then(|position| crate::layout::MasterPosition::from_name(position))
+                    {
+                        Some(position) => Command::SetMasterPosition(position),
+                        None => return Self::master_usage(),
+                    }
+                }
+                _ => return Self::master_usage(),
+            },
+            "weight" => {
+                let step = match message.args.get(1) {
+                    Some(step) => step.parse::<f64>().ok().filter(|step| step.is_finite()),
+                    None => Some(crate::layout::DEFAULT_WEIGHT_STEP),
+                };
+
+                match (message.args.first().map(|arg| arg.as_str()), step) {
+                    (Some("inc"), Some(step)) => Command::AdjustWeight(step),
+                    (Some("dec"), Some(step)) => Command::AdjustWeight(-step),
+                    (Some("reset"), _) => Command::ResetWeight,
+                    _ => {
+                        return IpcResponse {
+                            success: false,
+                            message: "weight command requires: inc|dec [step] or reset".to_string(),
+                            data: None,
+                        };
+                    }
+                }
+            }
+            "column-width" => {
+                let step = match message.args.get(1) {
+                    Some(step) => step.parse::<f64>().ok().filter(|step| step.is_finite()),
+                    None => Some(crate::layout::DEFAULT_COLUMN_WIDTH_STEP),
+                };
+
+                match (message.args.first().map(|arg| arg.as_str()), step) {
+                    (Some("inc"), Some(step)) => Command::AdjustColumnWidth(step),
+                    (Some("dec"), Some(step)) => Command::AdjustColumnWidth(-step),
+                    _ => {
+                        return IpcResponse {
+                            success: false,
+                            message: "column-width command requires: inc|dec [step]".to_string(),
+                            data: None,
+                        };
+                    }
+                }
+            }
+            "gap" => {
+                let amount = message.args.get(1).map(|amount| {
+                    amount
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|amount| amount.is_finite())
+                });
+                match (message.args.first().map(|arg| arg.as_str()), amount) {
+                    (Some("inc"), None) => Command::AdjustGap(crate::layout::DEFAULT_GAP_STEP),
+                    (Some("dec"), None) => Command::AdjustGap(-crate::layout::DEFAULT_GAP_STEP),
+                    (Some("inc"), Some(Some(step))) => Command::AdjustGap(step),
+                    (Some("dec"), Some(Some(step))) => Command::AdjustGap(-step),
+                    (Some("set"), Some(Some(gap))) => Command::SetGap(gap),
+                    _ => {
+                        return IpcResponse {
+                            success: false,
+                            message: "gap command requires: inc|dec [step] or set <pixels>"
+                                .to_string(),
+                            data: None,
+                        };
+                    }
+                }
+            }
+            "layout-command" => match message.args.split_first() {
+                Some((command, args)) => Command::LayoutCommand(command.clone(), args.to_vec()),
+                None => {
+                    return IpcResponse {
+                        success: false,
+                        message: "layout-command requires: <command> [args...]".to_string(),
+                        data: None,
+                    };
+                }
+            },
+            "layout-auto" => Command::EnableLayoutRules,
+            "layout-undo" => Command::LayoutUndo,
+            "layout-redo" => Command::LayoutRedo,
+            "preset" => match (
+                message.args.first().map(|arg| arg.as_str()),
+                message.args.get(1),
+            ) {
+                (Some("save"), Some(name)) => {
+                    return Self::query(command_sender, |reply| {
+                        Command::SavePreset(name.clone(), Some(reply))
+                    })
+                    .await;
+                }
+                (Some("apply"), Some(name)) => {
+                    return Self::query(command_sender, |reply| {
+                        Command::ApplyPreset(name.clone(), Some(reply))
+                    })
+                    .await;
+                }
+                (Some("delete"), Some(name)) => {
+                    return Self::query(command_sender, |reply| {
+                        Command::DeletePreset(name.clone(), Some(reply))
+                    })
+                    .await;
+                }
+                (Some("list"), None) => {
+                    return Self::query(command_sender, |reply| Command::ListPresets(Some(reply)))
+                        .await;
+                }
+                _ => {
+                    return IpcResponse {
+                        success: false,
+                        message: "preset command requires: save|apply|delete <name> or list"
+                            .to_string(),
+                        data: None,
+                    };
+                }
+            },
+            "move-column" => match message.args.first().map(|arg| arg.as_str()) {
+                Some("left") => Command::MoveColumn(crate::hotkeys::Direction::Left),
+                Some("right") => Command::MoveColumn(crate::hotkeys::Direction::Right),
+                _ => {
+                    return IpcResponse {
+                        success: false,
+                        message: "move-column command requires: left|right".to_string(),
+                        data: None,
+                    };
+                }
+            },
+            "container" => match message.args.first().map(|arg| arg.as_str()) {
+                Some("split") => Command::SetContainerStyle(None),
+                Some("next") => Command::CycleContainer(true),
+                Some("prev") => Command::CycleContainer(false),
+                Some(style) => match crate::layout::ContainerStyle::from_name(style) {
+                    Some(style) => Command::SetContainerStyle(Some(style)),
+                    None => return Self::container_usage(),
+                },
+                None => return Self::container_usage(),
+            },
             "reload" => Command::ReloadConfig,
             "list" => Command::ListWindows,
-            "status" => Command::GetStatus,
+            "status" => {
+                return Self::query(command_sender, |reply| Command::GetStatus(Some(reply))).await;
+            }
+            "cycle" => match message.args.first().map(|arg| arg.as_str()) {
+                Some("next") => Command::CycleWindow(true),
+                Some("prev") => Command::CycleWindow(false),
+                _ => {
+                    return IpcResponse {
+                        success: false,
+                        message: "cycle command requires: next|prev".to_string(),
+                        data: None,
+                    };
+                }
+            },
             "quit" | "stop" => Command::Quit,
             "ping" => {
                 return IpcResponse {
@@ -243,10 +512,35 @@ impl IpcServer {
                             {"name": "focus", "args": ["window_id"], "description": "Focus a window"},
                             {"name": "close", "args": ["window_id"], "description": "Close a window"},
                             {"name": "move", "args": ["window_id", "x", "y", "width", "height"], "description": "Move and resize a window"},
+                            {"name": "swap", "args": ["window_id", "window_id"], "description": "Exchange the places of two windows"},
+                            {"name": "swap-main", "args": [], "description": "Swap the focused window with the first window of the workspace"},
+                            {"name": "move-first", "args": [], "description": "Move the focused window to the front of the window order"},
+                            {"name": "move-last", "args": [], "description": "Move the focused window to the back of the window order"},
+                            {"name": "presel", "args": ["direction|cancel", "ratio?"], "description": "Choose where the next BSP window is inserted"},
                             {"name": "toggle-layout", "args": [], "description": "Toggle between layout modes"},
+                            {"name": "layout", "args": ["name"], "description": "Switch to a layout by name"},
+                            {"name": "layout-next", "args": [], "description": "Switch to the next layout in the cycle"},
+                            {"name": "layout-prev", "args": [], "description": "Switch to the previous layout in the cycle"},
+                            {"name": "toggle-split", "args": [], "description": "Flip the orientation of the focused window's split"},
+                            {"name": "rotate", "args": ["degrees"], "description": "Rotate the BSP tree by 90, 180 or 270 degrees"},
+                            {"name": "mirror", "args": ["axis"], "description": "Mirror the BSP tree across the x or y axis"},
+                            {"name": "balance", "args": [], "description": "Give every BSP window the same area"},
+                            {"name": "resize", "args": ["direction", "pixels?"], "description": "Move the focused window's split in a direction"},
+                            {"name": "master", "args": ["inc|dec|position", "side?"], "description": "Change the master count or move the master area"},
+                            {"name": "weight", "args": ["inc|dec|reset", "step?"], "description": "Change the focused window's share of its master or stack area"},
+                            {"name": "column-width", "args": ["inc|dec", "step?"], "description": "Widen or narrow the focused Paper column"},
+                            {"name": "move-column", "args": ["left|right"], "description": "Move the focused Paper column along the strip"},
+                            {"name": "container", "args": ["tabbed|stacked|split|next|prev"], "description": "Group the focused window's split into tabs or rows, or cycle the group"},
+                            {"name": "gap", "args": ["inc|dec|set", "pixels?"], "description": "Grow, shrink or set the inner and outer gaps"},
+                            {"name": "layout-command", "args": ["command", "args..."], "description": "Send a command to the current layout"},
+                            {"name": "layout-auto", "args": [], "description": "Let the layout rules choose the layout again after a manual change"},
+                            {"name": "layout-undo", "args": [], "description": "Restore the arrangement before the last layout change"},
+                            {"name": "layout-redo", "args": [], "description": "Re-apply the last undone layout change"},
+                            {"name": "preset", "args": ["save|apply|delete|list", "name?"], "description": "Save, apply, delete or list named layout presets"},
                             {"name": "reload", "args": [], "description": "Reload configuration"},
                             {"name": "list", "args": [], "description": "List all windows"},
                             {"name": "status", "args": [], "description": "Get window manager status"},
+                            {"name": "cycle", "args": ["next|prev"], "description": "Show the next or previous window in the monocle layout"},
                             {"name": "ping", "args": [], "description": "Test connection"},
                             {"name": "quit", "args": [], "description": "Stop the window manager"},
                             {"name": "help", "args": [], "description": "Show this help"}
@@ -282,6 +576,68 @@ impl IpcServer {
     }
 }
 
+impl IpcServer {
+    /// Send a command that answers on a reply channel and wait for its data.
+    async fn query<F>(command_sender: &mpsc::Sender<Command>, command: F) -> IpcResponse
+    where
+        F: FnOnce(crate::window_manager::Reply) -> Command,
+    {
+        let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+        if let Err(e) = command_sender.send(command(reply_tx)).await {
+            return IpcResponse {
+                success: false,
+                message: format!("Failed to send command: {}", e),
+                data: None,
+            };
+        }
+
+        match timeout(Duration::from_secs(5), reply_rx).await {
+            Ok(Ok(data)) => match data.get("error").and_then(|error| error.as_str()) {
+                Some(error) => IpcResponse {
+                    success: false,
+                    message: error.to_string(),
+                    data: None,
+                },
+                None => IpcResponse {
+                    success: true,
+                    message: "OK".to_string(),
+                    data: Some(data),
+                },
+            },
+            _ => IpcResponse {
+                success: false,
+                message: "Window manager did not answer".to_string(),
+                data: None,
+            },
+        }
+    }
+
+    fn presel_usage() -> IpcResponse {
+        IpcResponse {
+            success: false,
+            message: "presel command requires: left|right|up|down [ratio] or cancel".to_string(),
+            data: None,
+        }
+    }
+
+    fn container_usage() -> IpcResponse {
+        IpcResponse {
+            success: false,
+            message: "container command requires: tabbed|stacked|split|next|prev".to_string(),
+            data: None,
+        }
+    }
+
+    fn master_usage() -> IpcResponse {
+        IpcResponse {
+            success: false,
+            message: "master command requires: inc, dec or position left|right|top|bottom"
+                .to_string(),
+            data: None,
+        }
+    }
+}
+
 pub struct IpcClient {
     socket_path: String,
 }
@@ -354,10 +710,153 @@ impl IpcClient {
         .await
     }
 
+    pub async fn swap_windows(&self, a: WindowId, b: WindowId) -> Result<IpcResponse> {
+        self.send_command("swap", vec![a.0.to_string(), b.0.to_string()])
+            .await
+    }
+
+    pub async fn swap_main(&self) -> Result<IpcResponse> {
+        self.send_command("swap-main", vec![]).await
+    }
+
+    pub async fn move_to_first(&self) -> Result<IpcResponse> {
+        self.send_command("move-first", vec![]).await
+    }
+
+    pub async fn move_to_last(&self) -> Result<IpcResponse> {
+        self.send_command("move-last", vec![]).await
+    }
+
+    pub async fn preselect(&self, direction: &str, ratio: Option<f64>) -> Result<IpcResponse> {
+        let mut args = vec![direction.to_string()];
+        if let Some(ratio) = ratio {
+            args.push(ratio.to_string());
+        }
+        self.send_command("presel", args).await
+    }
+
     pub async fn toggle_layout(&self) -> Result<IpcResponse> {
         self.send_command("toggle-layout", vec![]).await
     }
 
+    pub async fn set_layout(&self, name: &str) -> Result<IpcResponse> {
+        self.send_command("layout", vec![name.to_string()]).await
+    }
+
+    pub async fn next_layout(&self) -> Result<IpcResponse> {
+        self.send_command("layout-next", vec![]).await
+    }
+
+    pub async fn previous_layout(&self) -> Result<IpcResponse> {
+        self.send_command("layout-prev", vec![]).await
+    }
+
+    pub async fn toggle_split(&self) -> Result<IpcResponse> {
+        self.send_command("toggle-split", vec![]).await
+    }
+
+    pub async fn rotate(&self, degrees: u32) -> Result<IpcResponse> {
+        self.send_command("rotate", vec![degrees.to_string()]).await
+    }
+
+    pub async fn mirror(&self, axis: &str) -> Result<IpcResponse> {
+        self.send_command("mirror", vec![axis.to_string()]).await
+    }
+
+    pub async fn balance(&self) -> Result<IpcResponse> {
+        self.send_command("balance", vec![]).await
+    }
+
+    pub async fn resize(&self, direction: &str, pixels: Option<f64>) -> Result<IpcResponse> {
+        let mut args = vec![direction.to_string()];
+        if let Some(pixels) = pixels {
+            args.push(pixels.to_string());
+        }
+        self.send_command("resize", args).await
+    }
+
+    pub async fn adjust_column_width(
+        &self,
+        action: &str,
+        step: Option<f64>,
+    ) -> Result<IpcResponse> {
+        let mut args = vec![action.to_string()];
+        if let Some(step) = step {
+            args.push(step.to_string());
+        }
+        self.send_command("column-width", args).await
+    }
+
+    pub async fn adjust_gap(&self, action: &str, pixels: Option<f64>) -> Result<IpcResponse> {
+        let mut args = vec![action.to_string()];
+        if let Some(pixels) = pixels {
+            args.push(pixels.to_string());
+        }
+        self.send_command("gap", args).await
+    }
+
+    pub async fn layout_command(&self, command: &str, args: Vec<String>) -> Result<IpcResponse> {
+        let mut all_args = vec![command.to_string()];
+        all_args.extend(args);
+        self.send_command("layout-command", all_args).await
+    }
+
+    pub async fn layout_auto(&self) -> Result<IpcResponse> {
+        self.send_command("layout-auto", vec![]).await
+    }
+
+    pub async fn layout_undo(&self) -> Result<IpcResponse> {
+        self.send_command("layout-undo", vec![]).await
+    }
+
+    pub async fn layout_redo(&self) -> Result<IpcResponse> {
+        self.send_command("layout-redo", vec![]).await
+    }
+
+    pub async fn preset(&self, action: &str, name: Option<&str>) -> Result<IpcResponse> {
+        let mut args = vec![action.to_string()];
+        if let Some(name) = name {
+            args.push(name.to_string());
+        }
+        self.send_command("preset", args).await
+    }
+
+    pub async fn move_column(&self, direction: &str) -> Result<IpcResponse> {
+        self.send_command("move-column", vec![direction.to_string()])
+            .await
+    }
+
+    pub async fn cycle(&self, direction: &str) -> Result<IpcResponse> {
+        self.send_command("cycle", vec![direction.to_string()])
+            .await
+    }
+
+    pub async fn container(&self, action: &str) -> Result<IpcResponse> {
+        self.send_command("container", vec![action.to_string()])
+            .await
+    }
+
+    pub async fn inc_master(&self) -> Result<IpcResponse> {
+        self.send_command("master", vec!["inc".to_string()]).await
+    }
+
+    pub async fn dec_master(&self) -> Result<IpcResponse> {
+        self.send_command("master", vec!["dec".to_string()]).await
+    }
+
+    pub async fn set_master_position(&self, position: &str) -> Result<IpcResponse> {
+        self.send_command("master", vec!["position".to_string(), position.to_string()])
+            .await
+    }
+
+    pub async fn adjust_weight(&self, action: &str, step: Option<f64>) -> Result<IpcResponse> {
+        let mut args = vec![action.to_string()];
+        if let Some(step) = step {
+            args.push(step.to_string());
+        }
+        self.send_command("weight", args).await
+    }
+
     pub async fn reload_config(&self) -> Result<IpcResponse> {
         self.send_command("reload", vec![]).await
     }
@@ -1,51 +1,551 @@
-use crate::config::{GeneralConfig, LayoutConfig};
-use crate::{Rect, Window, WindowId};
-use std::collections::HashMap;
+use crate::config::{
+    GeneralConfig, HotkeyConfig, LayoutConfig, LayoutRule, LayoutTemplate, TemplateArea,
+};
+use crate::hotkeys::Direction;
+use crate::preset::{LayoutPreset, PresetNode, WindowMatcher};
+use crate::{Rect, Result, SizeHints, Window, WindowId};
+use log::{error, warn};
+use std::collections::{HashMap, HashSet, VecDeque};
 
-#[derive(Debug, Clone)]
-pub enum LayoutType {
-    BSP,
+/// A tiling algorithm. Built-in layouts and layouts from plugins implement
+/// this and register with the `LayoutManager`, which cycles through them in
+/// registration order.
+pub trait Layout {
+    /// Name used in the config file, hotkey actions and IPC.
+    fn name(&self) -> &str;
+
+    /// Other names the layout answers to.
+    fn aliases(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Rects for `windows` inside `area`, with `gap` between neighbours.
+    /// Tiles are expected to be inset by half the gap on every side. The
+    /// manager gives access to shared settings such as the split ratio and
+    /// master count. On error the manager logs it and tiles with BSP
+    /// instead.
+    fn compute(
+        &mut self,
+        manager: &mut LayoutManager,
+        windows: &[&Window],
+        area: Rect,
+        gap: f64,
+    ) -> Result<HashMap<WindowId, Rect>>;
+
+    /// Whether the layout places windows at all. Size hints and gaps are
+    /// not applied to layouts that leave windows where they are.
+    fn tiles(&self) -> bool {
+        true
+    }
+
+    /// Whether the layout has to be recomputed when focus changes.
+    fn follows_focus(&self) -> bool {
+        false
+    }
+
+    /// Handle a command addressed to this layout, returning whether the
+    /// windows need to be re-tiled.
+    fn command(&mut self, command: &str, _args: &[String]) -> Result<bool> {
+        Err(anyhow::anyhow!(
+            "Layout {} has no command '{}'",
+            self.name(),
+            command
+        ))
+    }
+
+    /// Drop any state kept for a window that has been closed.
+    fn forget_window(&mut self, _window_id: WindowId) {}
+}
+
+pub const BSP_LAYOUT: &str = "bsp";
+pub const STACK_LAYOUT: &str = "stack";
+pub const CENTERED_MASTER_LAYOUT: &str = "centered_master";
+pub const THREE_COLUMN_LAYOUT: &str = "three_column";
+pub const GRID_LAYOUT: &str = "grid";
+pub const SPIRAL_LAYOUT: &str = "spiral";
+pub const DWINDLE_LAYOUT: &str = "dwindle";
+pub const COLUMN_LAYOUT: &str = "column";
+pub const PAPER_LAYOUT: &str = "paper";
+pub const MONOCLE_LAYOUT: &str = "monocle";
+pub const FLOAT_LAYOUT: &str = "float";
+
+type ComputeFn = fn(&mut LayoutManager, &[&Window], Rect, f64) -> HashMap<WindowId, Rect>;
+
+/// A layout that ships with skew, computed by one of the manager's own
+/// methods.
+struct BuiltinLayout {
+    name: &'static str,
+    aliases: &'static [&'static str],
+    compute: ComputeFn,
+    tiles: bool,
+    follows_focus: bool,
+}
+
+/// Holds a layout's place in the registry while the layout itself is
+/// lifted out to run, so it can still be looked up as the current layout.
+struct RunningLayout {
+    name: String,
+    tiles: bool,
+    follows_focus: bool,
+}
+
+impl Layout for RunningLayout {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn compute(
+        &mut self,
+        _manager: &mut LayoutManager,
+        _windows: &[&Window],
+        _area: Rect,
+        _gap: f64,
+    ) -> Result<HashMap<WindowId, Rect>> {
+        Err(anyhow::anyhow!("Layout {} is already running", self.name))
+    }
+
+    fn tiles(&self) -> bool {
+        self.tiles
+    }
+
+    fn follows_focus(&self) -> bool {
+        self.follows_focus
+    }
+}
+
+impl BuiltinLayout {
+    fn new(name: &'static str, aliases: &'static [&'static str], compute: ComputeFn) -> Self {
+        Self {
+            name,
+            aliases,
+            compute,
+            tiles: true,
+            follows_focus: false,
+        }
+    }
+
+    /// The built-in layouts in their default cycle order.
+    fn all() -> Vec<Self> {
+        vec![
+            Self::new(BSP_LAYOUT, &["binary"], |m, w, a, g| {
+                m.compute_bsp_layout(w, a, g)
+            }),
+            Self::new(STACK_LAYOUT, &["stacking"], |m, w, a, g| {
+                m.compute_stack_layout(w, a, g)
+            }),
+            Self::new(CENTERED_MASTER_LAYOUT, &["centeredmaster"], |m, w, a, g| {
+                m.compute_centered_master_layout(w, a, g)
+            }),
+            Self::new(THREE_COLUMN_LAYOUT, &["threecol"], |m, w, a, g| {
+                m.compute_three_column_layout(w, a, g)
+            }),
+            Self::new(GRID_LAYOUT, &[], |m, w, a, g| {
+                m.compute_grid_layout(w, a, g)
+            }),
+            Self::new(SPIRAL_LAYOUT, &["fibonacci"], |m, w, a, g| {
+                m.compute_spiral_layout(w, a, g)
+            }),
+            Self::new(DWINDLE_LAYOUT, &[], |m, w, a, g| {
+                m.compute_dwindle_layout(w, a, g)
+            }),
+            Self::new(COLUMN_LAYOUT, &["columns"], |m, w, a, g| {
+                m.compute_column_layout(w, a, g)
+            }),
+            Self {
+                follows_focus: true,
+                ..Self::new(PAPER_LAYOUT, &["scrolling"], |m, w, a, g| {
+                    m.compute_paper_layout(w, a, g)
+                })
+            },
+            Self {
+                follows_focus: true,
+                ..Self::new(MONOCLE_LAYOUT, &["fullscreen"], |m, w, a, g| {
+                    m.compute_monocle_layout(w, a, g)
+                })
+            },
+            Self {
+                tiles: false,
+                ..Self::new(FLOAT_LAYOUT, &["floating"], |m, w, a, g| {
+                    m.compute_float_layout(w, a, g)
+                })
+            },
+        ]
+    }
+}
+
+/// Whether `name` is taken by a built-in layout, as its name or an alias.
+pub fn is_builtin_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    BuiltinLayout::all()
+        .iter()
+        .any(|layout| layout.name == name || layout.aliases.contains(&name.as_str()))
+}
+
+impl Layout for BuiltinLayout {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn aliases(&self) -> &[&str] {
+        self.aliases
+    }
+
+    fn compute(
+        &mut self,
+        manager: &mut LayoutManager,
+        windows: &[&Window],
+        area: Rect,
+        gap: f64,
+    ) -> Result<HashMap<WindowId, Rect>> {
+        Ok((self.compute)(manager, windows, area, gap))
+    }
+
+    fn tiles(&self) -> bool {
+        self.tiles
+    }
+
+    fn follows_focus(&self) -> bool {
+        self.follows_focus
+    }
+}
+
+/// Where a template layout puts windows beyond its last slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TemplateOverflow {
     Stack,
-    Float,
-    Grid,
-    Spiral,
-    Column,
     Monocle,
+    Float,
+}
+
+impl TemplateOverflow {
+    fn from_string(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "monocle" => Self::Monocle,
+            "float" => Self::Float,
+            _ => Self::Stack,
+        }
+    }
+}
+
+/// A layout defined in the config file as a grid of named areas. Windows
+/// fill the areas in slot order; slots without a window stay empty.
+struct TemplateLayout {
+    name: String,
+    rows: Vec<f64>,
+    columns: Vec<f64>,
+    slots: Vec<TemplateArea>,
+    overflow: TemplateOverflow,
+}
+
+impl TemplateLayout {
+    fn from_config(template: &LayoutTemplate) -> Result<Self> {
+        Ok(Self {
+            name: template.name.to_lowercase(),
+            rows: template.rows.clone(),
+            columns: template.columns.clone(),
+            slots: template.slot_areas()?,
+            overflow: TemplateOverflow::from_string(&template.overflow),
+        })
+    }
+
+    /// Offsets of each track edge along an extent, from fractional sizes.
+    fn track_edges(sizes: &[f64], start: f64, extent: f64) -> Vec<f64> {
+        let total: f64 = sizes.iter().sum();
+        let mut edges = vec![start];
+        let mut offset = start;
+        for size in sizes {
+            offset += extent * size / total;
+            edges.push(offset);
+        }
+        edges
+    }
+}
+
+impl Layout for TemplateLayout {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn compute(
+        &mut self,
+        _manager: &mut LayoutManager,
+        windows: &[&Window],
+        area: Rect,
+        gap: f64,
+    ) -> Result<HashMap<WindowId, Rect>> {
+        let row_edges = Self::track_edges(&self.rows, area.y, area.height);
+        let column_edges = Self::track_edges(&self.columns, area.x, area.width);
+        let cells: Vec<Rect> = self
+            .slots
+            .iter()
+            .map(|slot| {
+                let x = column_edges[slot.columns.start];
+                let y = row_edges[slot.rows.start];
+                Rect::new(
+                    x,
+                    y,
+                    column_edges[slot.columns.end] - x,
+                    row_edges[slot.rows.end] - y,
+                )
+            })
+            .collect();
+
+        // Windows beyond the slot count share the last slot with its own
+        // window, which comes first
+        let last_slot = cells.len() - 1;
+        let sharing = windows.len().saturating_sub(last_slot);
+        let mut rects = HashMap::new();
+        for (i, window) in windows.iter().enumerate() {
+            let rect = if i < last_slot || sharing <= 1 {
+                cells[i]
+            } else {
+                let cell = cells[last_slot];
+                let index = i - last_slot;
+                match self.overflow {
+                    TemplateOverflow::Monocle => cell,
+                    TemplateOverflow::Float if index > 0 => {
+                        rects.insert(window.id, window.rect);
+                        continue;
+                    }
+                    TemplateOverflow::Float => cell,
+                    TemplateOverflow::Stack => {
+                        // Split along the slot's longer side
+                        let count = sharing as f64;
+                        if cell.width >= cell.height {
+                            let width = cell.width / count;
+                            Rect::new(cell.x + index as f64 * width, cell.y, width, cell.height)
+                        } else {
+                            let height = cell.height / count;
+                            Rect::new(cell.x, cell.y + index as f64 * height, cell.width, height)
+                        }
+                    }
+                }
+            };
+
+            rects.insert(
+                window.id,
+                Rect::new(
+                    rect.x + gap / 2.0,
+                    rect.y + gap / 2.0,
+                    rect.width - gap,
+                    rect.height - gap,
+                ),
+            );
+        }
+        Ok(rects)
+    }
+}
+
+/// Pixels a `resize_*` action moves a split by when no amount is given.
+pub const DEFAULT_RESIZE_STEP: f64 = 20.0;
+
+/// How the orientation of a new BSP split is chosen. `is_horizontal` on a
+/// node means its children sit side by side, so `FixedHorizontal` always
+/// produces left/right pairs and `FixedVertical` top/bottom pairs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitMode {
+    LongestSide,
+    Alternate,
+    FixedHorizontal,
+    FixedVertical,
 }
 
-impl LayoutType {
+impl SplitMode {
     fn from_string(s: &str) -> Self {
         match s.to_lowercase().as_str() {
-            "bsp" | "binary" => Self::BSP,
-            "stack" | "stacking" => Self::Stack,
-            "float" | "floating" => Self::Float,
-            "grid" => Self::Grid,
-            "spiral" => Self::Spiral,
-            "column" | "columns" => Self::Column,
-            "monocle" | "fullscreen" => Self::Monocle,
-            _ => Self::BSP,
+            "longest_side" | "auto" => Self::LongestSide,
+            "alternate" | "alternating" => Self::Alternate,
+            "fixed_horizontal" | "horizontal" => Self::FixedHorizontal,
+            "fixed_vertical" | "vertical" => Self::FixedVertical,
+            _ => Self::LongestSide,
         }
     }
 
     pub fn name(&self) -> &'static str {
         match self {
-            Self::BSP => "BSP",
-            Self::Stack => "Stack",
-            Self::Float => "Float",
-            Self::Grid => "Grid",
-            Self::Spiral => "Spiral",
-            Self::Column => "Column",
-            Self::Monocle => "Monocle",
+            Self::LongestSide => "longest_side",
+            Self::Alternate => "alternate",
+            Self::FixedHorizontal => "fixed_horizontal",
+            Self::FixedVertical => "fixed_vertical",
+        }
+    }
+
+    /// Orientation for splitting a leaf occupying `rect` whose parent split
+    /// has orientation `parent_horizontal` (`None` for the root).
+    pub fn is_horizontal(&self, rect: &Rect, parent_horizontal: Option<bool>) -> bool {
+        match self {
+            Self::LongestSide => rect.width >= rect.height,
+            Self::Alternate => match parent_horizontal {
+                Some(parent_horizontal) => !parent_horizontal,
+                None => rect.width >= rect.height,
+            },
+            Self::FixedHorizontal => true,
+            Self::FixedVertical => false,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// Order the Grid layout fills its cells in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GridOrder {
+    RowMajor,
+    ColumnMajor,
+}
+
+impl GridOrder {
+    fn from_string(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "column_major" | "columns" => Self::ColumnMajor,
+            _ => Self::RowMajor,
+        }
+    }
+}
+
+/// Side of the screen the master area occupies in the Stack layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MasterPosition {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl MasterPosition {
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "left" => Some(Self::Left),
+            "right" => Some(Self::Right),
+            "top" => Some(Self::Top),
+            "bottom" => Some(Self::Bottom),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::Top => "top",
+            Self::Bottom => "bottom",
+        }
+    }
+
+    /// Whether the master and stack areas sit side by side.
+    pub fn is_horizontal(&self) -> bool {
+        matches!(self, Self::Left | Self::Right)
+    }
+}
+
+/// Amount an `inc_weight`/`dec_weight` action changes a window's weight by
+/// when no step is given.
+pub const DEFAULT_WEIGHT_STEP: f64 = 0.25;
+
+/// Fraction of the screen width a `column_wider`/`column_narrower` action
+/// changes the focused Paper column by when no step is given.
+pub const DEFAULT_COLUMN_WIDTH_STEP: f64 = 0.1;
+
+/// Pixels a `gap_inc`/`gap_dec` action changes the gaps by when no step is
+/// given.
+pub const DEFAULT_GAP_STEP: f64 = 2.0;
+
+const MIN_WEIGHT: f64 = 0.25;
+const MIN_COLUMN_WIDTH: f64 = 0.1;
+const MAX_WEIGHT: f64 = 4.0;
+
+/// Clockwise rotation applied to a BSP tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+impl Rotation {
+    pub fn from_degrees(s: &str) -> Option<Self> {
+        match s.trim() {
+            "90" | "-270" => Some(Self::Deg90),
+            "180" | "-180" => Some(Self::Deg180),
+            "270" | "-90" => Some(Self::Deg270),
+            _ => None,
+        }
+    }
+}
+
+/// Axis a BSP tree is mirrored across: `X` swaps top and bottom, `Y` swaps
+/// left and right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MirrorAxis {
+    X,
+    Y,
+}
+
+impl MirrorAxis {
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "x" | "horizontal" => Some(Self::X),
+            "y" | "vertical" => Some(Self::Y),
+            _ => None,
+        }
+    }
+}
+
+/// Where the next BSP window lands relative to `target`, set by the
+/// `presel` command and consumed by the next insertion.
+#[derive(Debug, Clone, Copy)]
+pub struct Preselection {
+    pub target: WindowId,
+    pub direction: Direction,
+    pub ratio: Option<f64>,
+}
+
+/// How the windows of a group sharing one BSP tile are presented.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerStyle {
+    Tabbed,
+    Stacked,
+}
+
+impl ContainerStyle {
+    pub fn from_name(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "tabbed" | "tabs" => Some(Self::Tabbed),
+            "stacked" | "stacking" => Some(Self::Stacked),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Tabbed => "tabbed",
+            Self::Stacked => "stacked",
+        }
+    }
+}
+
+/// Several windows sharing one BSP leaf, i3-style. Only the leaf's
+/// `window_id` is shown; the other members are parked off screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowGroup {
+    pub style: ContainerStyle,
+    pub windows: Vec<WindowId>,
+}
+
+/// Area reserved above a group's visible window for one member's title: a
+/// tab in a tabbed group, a row in a stacked one.
+#[derive(Debug, Clone, Copy)]
+pub struct TitleBar {
+    pub window_id: WindowId,
+    pub rect: Rect,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct BSPNode {
     pub rect: Rect,
     pub split_ratio: f64,
     pub is_horizontal: bool,
     pub window_id: Option<WindowId>,
+    pub group: Option<WindowGroup>,
     pub left: Option<Box<BSPNode>>,
     pub right: Option<Box<BSPNode>>,
 }
@@ -57,404 +557,3104 @@ impl BSPNode {
             split_ratio: 0.5,
             is_horizontal: true,
             window_id: Some(window_id),
+            group: None,
+            left: None,
+            right: None,
+        }
+    }
+
+    pub fn new_container(rect: Rect, is_horizontal: bool, split_ratio: f64) -> Self {
+        Self {
+            rect,
+            split_ratio,
+            is_horizontal,
+            window_id: None,
+            group: None,
             left: None,
             right: None,
         }
     }
 
-    pub fn new_container(rect: Rect, is_horizontal: bool, split_ratio: f64) -> Self {
-        Self {
-            rect,
-            split_ratio,
-            is_horizontal,
-            window_id: None,
-            left: None,
-            right: None,
-        }
+    pub fn is_leaf(&self) -> bool {
+        self.left.is_none() && self.right.is_none()
+    }
+
+    /// Whether this node shows `window_id` or holds it as a hidden group
+    /// member.
+    fn holds_window(&self, window_id: WindowId) -> bool {
+        self.window_id == Some(window_id)
+            || self
+                .group
+                .as_ref()
+                .is_some_and(|group| group.windows.contains(&window_id))
+    }
+
+    /// Rects of the two children given this node's rect, orientation and ratio.
+    fn child_rects(&self) -> (Rect, Rect) {
+        self.split_rect(self.rect, self.split_ratio)
+    }
+
+    /// `rect` split along this node's orientation at `ratio`.
+    fn split_rect(&self, rect: Rect, ratio: f64) -> (Rect, Rect) {
+        if self.is_horizontal {
+            let left_width = rect.width * ratio;
+            (
+                Rect::new(rect.x, rect.y, left_width, rect.height),
+                Rect::new(
+                    rect.x + left_width,
+                    rect.y,
+                    rect.width - left_width,
+                    rect.height,
+                ),
+            )
+        } else {
+            let left_height = rect.height * ratio;
+            (
+                Rect::new(rect.x, rect.y, rect.width, left_height),
+                Rect::new(
+                    rect.x,
+                    rect.y + left_height,
+                    rect.width,
+                    rect.height - left_height,
+                ),
+            )
+        }
+    }
+
+    /// Assign a new rect to this node and recompute the rects of its subtree.
+    pub fn update_rects(&mut self, rect: Rect) {
+        self.rect = rect;
+        if self.is_leaf() {
+            return;
+        }
+
+        let (left_rect, right_rect) = self.child_rects();
+        if let Some(ref mut left) = self.left {
+            left.update_rects(left_rect);
+        }
+        if let Some(ref mut right) = self.right {
+            right.update_rects(right_rect);
+        }
+    }
+
+    pub fn insert_window(&mut self, window_id: WindowId, split_ratio: f64) {
+        if self.is_leaf() {
+            if self.window_id.is_some() {
+                let is_horizontal = self.is_horizontal;
+                self.split_leaf(window_id, split_ratio, is_horizontal, false);
+            } else {
+                self.window_id = Some(window_id);
+            }
+        } else if let Some(ref mut right) = self.right {
+            right.insert_window(window_id, split_ratio);
+        }
+    }
+
+    /// Turn this leaf into a container holding its current window and
+    /// `window_id`. With `new_first` the new window takes the left/top child.
+    pub fn split_leaf(
+        &mut self,
+        window_id: WindowId,
+        split_ratio: f64,
+        is_horizontal: bool,
+        new_first: bool,
+    ) {
+        let existing_id = match self.window_id.take() {
+            Some(id) => id,
+            None => {
+                self.window_id = Some(window_id);
+                return;
+            }
+        };
+        let group = self.group.take();
+
+        self.split_ratio = split_ratio;
+        self.is_horizontal = is_horizontal;
+        let (left_rect, right_rect) = self.child_rects();
+        let (first, second) = if new_first {
+            (window_id, existing_id)
+        } else {
+            (existing_id, window_id)
+        };
+
+        self.left = Some(Box::new(BSPNode::new_leaf(first, left_rect)));
+        self.right = Some(Box::new(BSPNode::new_leaf(second, right_rect)));
+
+        // A group keeps all of its members when a window is split off beside it
+        let existing = if new_first {
+            &mut self.right
+        } else {
+            &mut self.left
+        };
+        if let Some(existing) = existing {
+            existing.group = group;
+        }
+    }
+
+    /// Split this leaf so `window_id` lands on the `direction` side of the
+    /// window already here.
+    pub fn split_leaf_towards(&mut self, window_id: WindowId, direction: Direction, ratio: f64) {
+        let is_horizontal = matches!(direction, Direction::Left | Direction::Right);
+        let new_first = matches!(direction, Direction::Left | Direction::Up);
+        self.split_leaf(window_id, ratio, is_horizontal, new_first);
+    }
+
+    pub fn find_leaf_mut(&mut self, window_id: WindowId) -> Option<&mut BSPNode> {
+        if self.window_id == Some(window_id) {
+            return Some(self);
+        }
+
+        if let Some(ref mut left) = self.left {
+            if let Some(node) = left.find_leaf_mut(window_id) {
+                return Some(node);
+            }
+        }
+        if let Some(ref mut right) = self.right {
+            if let Some(node) = right.find_leaf_mut(window_id) {
+                return Some(node);
+            }
+        }
+
+        None
+    }
+
+    /// The leaf whose group has `window_id` as a member, shown or hidden.
+    pub fn find_group_leaf_mut(&mut self, window_id: WindowId) -> Option<&mut BSPNode> {
+        if self.group.is_some() && self.holds_window(window_id) {
+            return Some(self);
+        }
+
+        if let Some(ref mut left) = self.left {
+            if let Some(node) = left.find_group_leaf_mut(window_id) {
+                return Some(node);
+            }
+        }
+        if let Some(ref mut right) = self.right {
+            if let Some(node) = right.find_group_leaf_mut(window_id) {
+                return Some(node);
+            }
+        }
+
+        None
+    }
+
+    /// Make `window_id` the visible member of its group. Returns whether the
+    /// visible window changed.
+    pub fn activate(&mut self, window_id: WindowId) -> bool {
+        match self.find_group_leaf_mut(window_id) {
+            Some(leaf) if leaf.window_id != Some(window_id) => {
+                leaf.window_id = Some(window_id);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Take `window_id` out of a group that has other members, showing its
+    /// neighbour if it was the visible one. The last member of a group is
+    /// left for `remove_window`, which removes the whole leaf.
+    pub fn remove_from_group(&mut self, window_id: WindowId) -> bool {
+        let leaf = match self.find_group_leaf_mut(window_id) {
+            Some(leaf) => leaf,
+            None => return false,
+        };
+        let group = match leaf.group {
+            Some(ref mut group) if group.windows.len() > 1 => group,
+            _ => return false,
+        };
+
+        if let Some(index) = group.windows.iter().position(|id| *id == window_id) {
+            group.windows.remove(index);
+            if leaf.window_id == Some(window_id) {
+                leaf.window_id = Some(group.windows[index.min(group.windows.len() - 1)]);
+            }
+        }
+        true
+    }
+
+    /// Show the next (or previous) member of this leaf's group, wrapping
+    /// around. Returns the newly visible window.
+    pub fn cycle_group(&mut self, forward: bool) -> Option<WindowId> {
+        let group = self.group.as_ref()?;
+        let count = group.windows.len();
+        let index = group
+            .windows
+            .iter()
+            .position(|id| Some(*id) == self.window_id)?;
+        let next = if forward {
+            (index + 1) % count
+        } else {
+            (index + count - 1) % count
+        };
+
+        self.window_id = Some(group.windows[next]);
+        self.window_id
+    }
+
+    /// Remove a window from below this node, promoting its sibling into the
+    /// parent's place. Only the promoted subtree's rects are recomputed.
+    /// A leaf cannot remove itself; the owner of the root handles that case.
+    pub fn remove_window(&mut self, window_id: WindowId) -> bool {
+        if self.is_leaf() {
+            return false;
+        }
+
+        let in_left = self
+            .left
+            .as_ref()
+            .is_some_and(|n| n.window_id == Some(window_id));
+        let in_right = self
+            .right
+            .as_ref()
+            .is_some_and(|n| n.window_id == Some(window_id));
+
+        if in_left || in_right {
+            let sibling = if in_left {
+                self.right.take()
+            } else {
+                self.left.take()
+            };
+            let rect = self.rect;
+
+            match sibling {
+                Some(sibling) => *self = *sibling,
+                None => {
+                    self.left = None;
+                    self.right = None;
+                    self.window_id = None;
+                }
+            }
+            self.update_rects(rect);
+            return true;
+        }
+
+        if let Some(ref mut left) = self.left {
+            if left.remove_window(window_id) {
+                return true;
+            }
+        }
+        if let Some(ref mut right) = self.right {
+            if right.remove_window(window_id) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// The container whose direct child is the leaf holding `window_id`.
+    pub fn find_parent(&self, window_id: WindowId) -> Option<&BSPNode> {
+        let is_child = |child: &Option<Box<BSPNode>>| {
+            child
+                .as_ref()
+                .is_some_and(|n| n.window_id == Some(window_id))
+        };
+        if is_child(&self.left) || is_child(&self.right) {
+            return Some(self);
+        }
+
+        self.left
+            .as_ref()
+            .and_then(|n| n.find_parent(window_id))
+            .or_else(|| self.right.as_ref().and_then(|n| n.find_parent(window_id)))
+    }
+
+    pub fn find_parent_mut(&mut self, window_id: WindowId) -> Option<&mut BSPNode> {
+        let is_child = |child: &Option<Box<BSPNode>>| {
+            child
+                .as_ref()
+                .is_some_and(|n| n.window_id == Some(window_id))
+        };
+        if is_child(&self.left) || is_child(&self.right) {
+            return Some(self);
+        }
+
+        if let Some(ref mut left) = self.left {
+            if let Some(node) = left.find_parent_mut(window_id) {
+                return Some(node);
+            }
+        }
+        if let Some(ref mut right) = self.right {
+            if let Some(node) = right.find_parent_mut(window_id) {
+                return Some(node);
+            }
+        }
+
+        None
+    }
+
+    /// The bottom-right-most leaf, reached by always following `right`.
+    pub fn last_window_id(&self) -> Option<WindowId> {
+        match self.right {
+            Some(ref right) => right.last_window_id(),
+            None => self.window_id,
+        }
+    }
+
+    fn swap_children(&mut self) {
+        std::mem::swap(&mut self.left, &mut self.right);
+        self.split_ratio = 1.0 - self.split_ratio;
+    }
+
+    /// Rotate every split below this node clockwise. Rects are left for the
+    /// caller to recompute with `update_rects`.
+    pub fn rotate(&mut self, rotation: Rotation) {
+        if self.is_leaf() {
+            return;
+        }
+
+        match rotation {
+            // A left|right split turns into top/bottom with the left child on
+            // top; a top/bottom split turns into left|right with top on the right.
+            Rotation::Deg90 => {
+                if !self.is_horizontal {
+                    self.swap_children();
+                }
+                self.is_horizontal = !self.is_horizontal;
+            }
+            Rotation::Deg180 => self.swap_children(),
+            Rotation::Deg270 => {
+                if self.is_horizontal {
+                    self.swap_children();
+                }
+                self.is_horizontal = !self.is_horizontal;
+            }
+        }
+
+        if let Some(ref mut left) = self.left {
+            left.rotate(rotation);
+        }
+        if let Some(ref mut right) = self.right {
+            right.rotate(rotation);
+        }
+    }
+
+    pub fn mirror(&mut self, axis: MirrorAxis) {
+        if self.is_leaf() {
+            return;
+        }
+
+        let swaps = match axis {
+            MirrorAxis::X => !self.is_horizontal,
+            MirrorAxis::Y => self.is_horizontal,
+        };
+        if swaps {
+            self.swap_children();
+        }
+
+        if let Some(ref mut left) = self.left {
+            left.mirror(axis);
+        }
+        if let Some(ref mut right) = self.right {
+            right.mirror(axis);
+        }
+    }
+
+    /// Set every split ratio so all leaves get the same area. Returns the
+    /// number of leaves below this node.
+    pub fn balance(&mut self) -> usize {
+        if self.is_leaf() {
+            return 1;
+        }
+
+        let left_leaves = self.left.as_mut().map_or(0, |n| n.balance());
+        let right_leaves = self.right.as_mut().map_or(0, |n| n.balance());
+        let total = left_leaves + right_leaves;
+        if total > 0 && left_leaves > 0 && right_leaves > 0 {
+            self.split_ratio = left_leaves as f64 / total as f64;
+        }
+
+        total
+    }
+
+    /// Branches taken from this node to the leaf holding `window_id`,
+    /// `false` for left and `true` for right.
+    pub fn path_to(&self, window_id: WindowId) -> Option<Vec<bool>> {
+        if self.window_id == Some(window_id) {
+            return Some(Vec::new());
+        }
+
+        for (is_right, child) in [(false, &self.left), (true, &self.right)] {
+            if let Some(mut path) = child.as_ref().and_then(|n| n.path_to(window_id)) {
+                path.insert(0, is_right);
+                return Some(path);
+            }
+        }
+
+        None
+    }
+
+    pub fn node_at(&self, path: &[bool]) -> Option<&BSPNode> {
+        match path.split_first() {
+            None => Some(self),
+            Some((false, rest)) => self.left.as_ref()?.node_at(rest),
+            Some((true, rest)) => self.right.as_ref()?.node_at(rest),
+        }
+    }
+
+    pub fn node_at_mut(&mut self, path: &[bool]) -> Option<&mut BSPNode> {
+        match path.split_first() {
+            None => Some(self),
+            Some((false, rest)) => self.left.as_mut()?.node_at_mut(rest),
+            Some((true, rest)) => self.right.as_mut()?.node_at_mut(rest),
+        }
+    }
+
+    /// Path to the deepest ancestor of `window_id` whose split has the given
+    /// orientation.
+    pub fn nearest_split_path(
+        &self,
+        window_id: WindowId,
+        is_horizontal: bool,
+    ) -> Option<Vec<bool>> {
+        let path = self.path_to(window_id)?;
+
+        (0..path.len())
+            .rev()
+            .map(|depth| &path[..depth])
+            .find_map(|prefix| {
+                self.node_at(prefix)
+                    .filter(|node| !node.is_leaf() && node.is_horizontal == is_horizontal)
+                    .map(|_| prefix.to_vec())
+            })
+    }
+
+    /// Path to the deepest ancestor split on the given axis whose divider
+    /// touches the window: with `window_second` the window sits in the
+    /// right/bottom child, so the divider is its left/top edge.
+    pub fn edge_split_path(
+        &self,
+        window_id: WindowId,
+        is_horizontal: bool,
+        window_second: bool,
+    ) -> Option<Vec<bool>> {
+        let path = self.path_to(window_id)?;
+
+        (0..path.len()).rev().find_map(|depth| {
+            let prefix = &path[..depth];
+            self.node_at(prefix)
+                .filter(|node| node.is_horizontal == is_horizontal)
+                .filter(|_| path[depth] == window_second)
+                .map(|_| prefix.to_vec())
+        })
+    }
+
+    pub fn contains_window(&self, window_id: WindowId) -> bool {
+        if self.holds_window(window_id) {
+            return true;
+        }
+
+        self.left
+            .as_ref()
+            .is_some_and(|n| n.contains_window(window_id))
+            || self
+                .right
+                .as_ref()
+                .is_some_and(|n| n.contains_window(window_id))
+    }
+
+    pub fn window_ids(&self) -> Vec<WindowId> {
+        let mut ids = Vec::new();
+        self.collect_window_ids(&mut ids);
+        ids
+    }
+
+    fn collect_window_ids(&self, ids: &mut Vec<WindowId>) {
+        if let Some(ref group) = self.group {
+            ids.extend(group.windows.iter().copied());
+        } else if let Some(window_id) = self.window_id {
+            ids.push(window_id);
+        }
+        if let Some(ref left) = self.left {
+            left.collect_window_ids(ids);
+        }
+        if let Some(ref right) = self.right {
+            right.collect_window_ids(ids);
+        }
+    }
+
+    /// Rects of the visible windows. Grouped windows give up the top of
+    /// their tile to the group's title bars. Where a split would squeeze a
+    /// window below its minimum size or stretch it past its maximum, the
+    /// split moves as little as needed; the stored ratios are left alone so
+    /// the tree returns to them once the constraints allow.
+    pub fn collect_window_rects(
+        &self,
+        hints: &HashMap<WindowId, SizeHints>,
+        gap: f64,
+        title_height: f64,
+    ) -> HashMap<WindowId, Rect> {
+        let mut rects = HashMap::new();
+        self.collect_rects_recursive(self.rect, hints, gap, title_height, &mut rects);
+        rects
+    }
+
+    fn collect_rects_recursive(
+        &self,
+        rect: Rect,
+        hints: &HashMap<WindowId, SizeHints>,
+        gap: f64,
+        title_height: f64,
+        rects: &mut HashMap<WindowId, Rect>,
+    ) {
+        if let Some(window_id) = self.window_id {
+            let title_area = self.title_area_height(title_height);
+            let adjusted_rect = Rect::new(
+                rect.x + gap / 2.0,
+                rect.y + gap / 2.0 + title_area,
+                rect.width - gap,
+                rect.height - gap - title_area,
+            );
+            rects.insert(window_id, adjusted_rect);
+        } else if let (Some(left), Some(right)) = (&self.left, &self.right) {
+            let horizontal = self.is_horizontal;
+            let extent = if horizontal { rect.width } else { rect.height };
+            let ((left_min, left_max), (right_min, right_max)) = (
+                left.size_bounds(horizontal, hints, gap, title_height),
+                right.size_bounds(horizontal, hints, gap, title_height),
+            );
+
+            let left_extent = extent * self.split_ratio;
+            let right_extent = extent - left_extent;
+            let ratio = if (left_min..=left_max).contains(&left_extent)
+                && (right_min..=right_max).contains(&right_extent)
+            {
+                self.split_ratio
+            } else {
+                let extents = distribute(
+                    extent,
+                    &[
+                        (self.split_ratio, left_min, left_max),
+                        (1.0 - self.split_ratio, right_min, right_max),
+                    ],
+                );
+                extents[0] / extent
+            };
+
+            let (left_rect, right_rect) = self.split_rect(rect, ratio);
+            left.collect_rects_recursive(left_rect, hints, gap, title_height, rects);
+            right.collect_rects_recursive(right_rect, hints, gap, title_height, rects);
+        }
+    }
+
+    /// Smallest and largest extent along one axis the subtree's windows
+    /// accept, including gaps and title bars. Side-by-side children add up
+    /// along the split axis; across it the most demanding child decides.
+    fn size_bounds(
+        &self,
+        horizontal: bool,
+        hints: &HashMap<WindowId, SizeHints>,
+        gap: f64,
+        title_height: f64,
+    ) -> (f64, f64) {
+        match (&self.left, &self.right) {
+            (Some(left), Some(right)) => {
+                let (left_min, left_max) = left.size_bounds(horizontal, hints, gap, title_height);
+                let (right_min, right_max) =
+                    right.size_bounds(horizontal, hints, gap, title_height);
+                if self.is_horizontal == horizontal {
+                    (left_min + right_min, left_max + right_max)
+                } else {
+                    (left_min.max(right_min), left_max.max(right_max))
+                }
+            }
+            _ => {
+                // Every member of a group takes a turn in the same tile
+                let title_area = if horizontal {
+                    0.0
+                } else {
+                    self.title_area_height(title_height)
+                };
+                let members = match self.group {
+                    Some(ref group) => group.windows.clone(),
+                    None => self.window_id.into_iter().collect(),
+                };
+                members
+                    .iter()
+                    .map(|window_id| {
+                        let window_hints = hints.get(window_id).copied().unwrap_or_default();
+                        let (min_width, min_height) = window_hints.min_size();
+                        let (max_width, max_height) = window_hints.max_size();
+                        if horizontal {
+                            (min_width, max_width)
+                        } else {
+                            (min_height, max_height)
+                        }
+                    })
+                    .fold((0.0, 0.0), |(min, max), (window_min, window_max)| {
+                        (
+                            f64::max(min, window_min + gap + title_area),
+                            f64::max(max, window_max + gap + title_area),
+                        )
+                    })
+            }
+        }
+    }
+
+    /// Height taken by title bars at the top of this leaf: one strip of tabs
+    /// for a tabbed group, one row per member for a stacked one.
+    fn title_area_height(&self, title_height: f64) -> f64 {
+        match self.group {
+            Some(WindowGroup {
+                style: ContainerStyle::Tabbed,
+                ..
+            }) => title_height,
+            Some(WindowGroup {
+                style: ContainerStyle::Stacked,
+                ref windows,
+            }) => title_height * windows.len() as f64,
+            None => 0.0,
+        }
+    }
+
+    /// Group members that are not currently shown.
+    pub fn hidden_window_ids(&self) -> Vec<WindowId> {
+        self.window_ids()
+            .into_iter()
+            .filter(|id| self.find_visible(*id).is_none())
+            .collect()
+    }
+
+    fn find_visible(&self, window_id: WindowId) -> Option<&BSPNode> {
+        if self.window_id == Some(window_id) {
+            return Some(self);
+        }
+
+        self.left
+            .as_ref()
+            .and_then(|n| n.find_visible(window_id))
+            .or_else(|| self.right.as_ref().and_then(|n| n.find_visible(window_id)))
+    }
+
+    pub fn collect_title_bars(&self, bars: &mut Vec<TitleBar>, gap: f64, title_height: f64) {
+        if let Some(ref group) = self.group {
+            let x = self.rect.x + gap / 2.0;
+            let y = self.rect.y + gap / 2.0;
+            let width = self.rect.width - gap;
+            let tab_width = width / group.windows.len() as f64;
+
+            for (i, window_id) in group.windows.iter().enumerate() {
+                let rect = match group.style {
+                    ContainerStyle::Tabbed => {
+                        Rect::new(x + i as f64 * tab_width, y, tab_width, title_height)
+                    }
+                    ContainerStyle::Stacked => {
+                        Rect::new(x, y + i as f64 * title_height, width, title_height)
+                    }
+                };
+                bars.push(TitleBar {
+                    window_id: *window_id,
+                    rect,
+                    active: self.window_id == Some(*window_id),
+                });
+            }
+        }
+
+        if let Some(ref left) = self.left {
+            left.collect_title_bars(bars, gap, title_height);
+        }
+        if let Some(ref right) = self.right {
+            right.collect_title_bars(bars, gap, title_height);
+        }
+    }
+}
+
+/// Which part of `rect` the point falls in when a window is dropped on it:
+/// `None` for the centre (swap), otherwise the edge to insert beside.
+pub fn drop_zone(rect: &Rect, x: f64, y: f64) -> Option<Direction> {
+    let (center_x, center_y) = rect.center();
+    let dx = (x - center_x) / (rect.width / 2.0).max(1.0);
+    let dy = (y - center_y) / (rect.height / 2.0).max(1.0);
+
+    if dx.abs() < 0.5 && dy.abs() < 0.5 {
+        None
+    } else if dx.abs() >= dy.abs() {
+        Some(if dx < 0.0 {
+            Direction::Left
+        } else {
+            Direction::Right
+        })
+    } else {
+        Some(if dy < 0.0 {
+            Direction::Up
+        } else {
+            Direction::Down
+        })
+    }
+}
+
+/// Share `extent` between items in proportion to their weights while
+/// keeping each within its `(weight, min, max)` bounds. Items pinned at a
+/// bound hand the difference to the rest. When the minimums alone exceed
+/// the extent they are scaled down together; when every item is at its
+/// maximum the leftover is still shared out so the items fill the extent.
+fn distribute(extent: f64, items: &[(f64, f64, f64)]) -> Vec<f64> {
+    let total_min: f64 = items.iter().map(|&(_, min, _)| min).sum();
+    if total_min >= extent {
+        return items
+            .iter()
+            .map(|&(_, min, _)| {
+                if total_min > 0.0 {
+                    extent * min / total_min
+                } else {
+                    extent / items.len() as f64
+                }
+            })
+            .collect();
+    }
+
+    let mut pinned: Vec<Option<f64>> = vec![None; items.len()];
+    loop {
+        let free = extent - pinned.iter().flatten().sum::<f64>();
+        let weight: f64 = items
+            .iter()
+            .zip(&pinned)
+            .filter(|(_, pin)| pin.is_none())
+            .map(|(&(weight, _, _), _)| weight.max(0.0))
+            .sum();
+        if weight <= 0.0 {
+            break;
+        }
+
+        let shares: Vec<f64> = items
+            .iter()
+            .map(|&(item_weight, _, _)| free * item_weight.max(0.0) / weight)
+            .collect();
+
+        // Pin items below their minimum first; settling those can only
+        // shrink the others, so maximums are checked once none remain
+        let mut changed = false;
+        for (i, &(_, min, _)) in items.iter().enumerate() {
+            if pinned[i].is_none() && shares[i] < min {
+                pinned[i] = Some(min);
+                changed = true;
+            }
+        }
+        if !changed {
+            for (i, &(_, _, max)) in items.iter().enumerate() {
+                if pinned[i].is_none() && shares[i] > max {
+                    pinned[i] = Some(max);
+                    changed = true;
+                }
+            }
+        }
+        if !changed {
+            return pinned
+                .iter()
+                .zip(shares)
+                .map(|(pin, share)| pin.unwrap_or(share))
+                .collect();
+        }
+    }
+
+    // Everything is pinned: give any leftover out by weight
+    let sizes: Vec<f64> = pinned.iter().map(|pin| pin.unwrap_or(0.0)).collect();
+    let leftover = extent - sizes.iter().sum::<f64>();
+    let weight: f64 = items.iter().map(|&(weight, _, _)| weight.max(0.0)).sum();
+    sizes
+        .iter()
+        .zip(items)
+        .map(|(size, &(item_weight, _, _))| {
+            if weight > 0.0 {
+                size + leftover * item_weight.max(0.0) / weight
+            } else {
+                size + leftover / items.len() as f64
+            }
+        })
+        .collect()
+}
+
+/// Minimum and maximum extent of a window's tile along one axis, counting
+/// `gap` of padding.
+fn window_bounds(window: &Window, horizontal: bool, gap: f64) -> (f64, f64) {
+    let (min_width, min_height) = window.size_hints.min_size();
+    let (max_width, max_height) = window.size_hints.max_size();
+    if horizontal {
+        (min_width + gap, max_width + gap)
+    } else {
+        (min_height + gap, max_height + gap)
+    }
+}
+
+/// Bounds of an area whose windows are laid out across the given axis: it
+/// must be as wide as its widest minimum and gains nothing beyond its
+/// widest maximum.
+fn area_bounds(windows: &[&Window], horizontal: bool, gap: f64) -> (f64, f64) {
+    windows
+        .iter()
+        .map(|window| window_bounds(window, horizontal, gap))
+        .fold((0.0, 0.0), |(min, max), (window_min, window_max)| {
+            (f64::max(min, window_min), f64::max(max, window_max))
+        })
+}
+
+/// Everything `layout_undo` brings back on a workspace: its current and
+/// picked layout, BSP tree, ratios, weights and window order. Focus and
+/// the windows themselves are not part of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutState {
+    current_layout: usize,
+    picked_layout: Option<usize>,
+    rules_off: bool,
+    bsp_root: Option<BSPNode>,
+    split_ratio: f64,
+    nmaster: usize,
+    master_position: MasterPosition,
+    window_weights: HashMap<WindowId, f64>,
+    three_column_ratios: [f64; 3],
+    column_width: f64,
+    column_widths: HashMap<WindowId, f64>,
+    paper_columns: Vec<WindowId>,
+    window_order: Vec<WindowId>,
+    monocle_order: Vec<WindowId>,
+}
+
+/// Arrangement and settings of a workspace that is not shown, put back
+/// when it is.
+#[derive(Debug, Clone)]
+struct WorkspaceLayout {
+    bsp_root: Option<BSPNode>,
+    split_ratio: f64,
+    preselection: Option<Preselection>,
+    last_focused: Option<WindowId>,
+    nmaster: usize,
+    master_position: MasterPosition,
+    window_weights: HashMap<WindowId, f64>,
+    three_column_ratios: [f64; 3],
+    column_width: f64,
+    column_widths: HashMap<WindowId, f64>,
+    paper_columns: Vec<WindowId>,
+    scroll_offset: f64,
+    monocle_order: Vec<WindowId>,
+    monocle_active: Option<WindowId>,
+}
+
+/// Undo and redo stacks of one workspace.
+#[derive(Debug, Default)]
+struct LayoutHistory {
+    undo: VecDeque<LayoutState>,
+    redo: Vec<LayoutState>,
+}
+
+pub struct LayoutManager {
+    layouts: Vec<Box<dyn Layout>>,
+    current_layout: usize,
+    default_layout: usize,
+    // Layout picked by hand on each workspace, used there when no rule
+    // applies
+    workspace_layouts: HashMap<u32, usize>,
+    // Names from `layout.cycle`; empty cycles through every layout
+    cycle: Vec<String>,
+    bsp_root: Option<BSPNode>,
+    split_ratio: f64,
+    split_mode: SplitMode,
+    preselection: Option<Preselection>,
+    // Last tiled window to have focus. New windows are usually focused
+    // before they are tiled, so BSP inserts them next to this one.
+    last_focused: Option<WindowId>,
+    nmaster: usize,
+    master_position: MasterPosition,
+    window_weights: HashMap<WindowId, f64>,
+    three_column_ratios: [f64; 3],
+    column_width: f64,
+    column_widths: HashMap<WindowId, f64>,
+    paper_columns: Vec<WindowId>,
+    scroll_offset: f64,
+    title_bar_height: f64,
+    // Windows of each workspace in the order layouts receive them: the
+    // order they appeared in, unless reordered by hand or by a preset
+    window_orders: HashMap<u32, Vec<WindowId>>,
+    monocle_order: Vec<WindowId>,
+    monocle_active: Option<WindowId>,
+    grid_rows: usize,
+    grid_columns: usize,
+    grid_order: GridOrder,
+    grid_stretch_last: bool,
+    history: HashMap<u32, LayoutHistory>,
+    history_size: usize,
+    rules: Vec<LayoutRule>,
+    // Workspaces whose layout was changed by hand, where rules stay off
+    manual_workspaces: HashSet<u32>,
+    // Workspace the BSP tree, ratios and columns belong to; those of the
+    // others are parked here until they are shown again
+    workspace: u32,
+    workspaces: HashMap<u32, WorkspaceLayout>,
+    // What a workspace starts out with the first time it is shown
+    new_workspace: WorkspaceLayout,
+    unfit_windows: Vec<WindowId>,
+    tiling_area: Rect,
+    inner_gap: f64,
+}
+
+impl LayoutManager {
+    pub fn new(config: &LayoutConfig) -> Self {
+        let master_position =
+            MasterPosition::from_name(&config.master_position).unwrap_or(MasterPosition::Left);
+        let three_column_ratios = match config.three_column_ratios[..] {
+            [left, middle, right] => [left, middle, right],
+            _ => [1.0, 1.0, 1.0],
+        };
+        let mut manager = Self {
+            layouts: Vec::new(),
+            current_layout: 0,
+            default_layout: 0,
+            workspace_layouts: HashMap::new(),
+            cycle: config.cycle.clone(),
+            bsp_root: None,
+            split_ratio: config.split_ratio,
+            split_mode: SplitMode::from_string(&config.split_mode),
+            preselection: None,
+            last_focused: None,
+            nmaster: config.nmaster,
+            master_position,
+            window_weights: HashMap::new(),
+            three_column_ratios,
+            column_width: config.column_width,
+            column_widths: HashMap::new(),
+            paper_columns: Vec::new(),
+            scroll_offset: 0.0,
+            title_bar_height: config.title_bar_height,
+            window_orders: HashMap::new(),
+            monocle_order: Vec::new(),
+            monocle_active: None,
+            grid_rows: config.grid_rows,
+            grid_columns: config.grid_columns,
+            grid_order: GridOrder::from_string(&config.grid_order),
+            grid_stretch_last: config.grid_stretch_last,
+            history: HashMap::new(),
+            history_size: config.history_size,
+            rules: config.rules.clone(),
+            manual_workspaces: HashSet::new(),
+            workspace: 1,
+            workspaces: HashMap::new(),
+            new_workspace: WorkspaceLayout {
+                bsp_root: None,
+                split_ratio: config.split_ratio,
+                preselection: None,
+                last_focused: None,
+                nmaster: config.nmaster,
+                master_position,
+                window_weights: HashMap::new(),
+                three_column_ratios,
+                column_width: config.column_width,
+                column_widths: HashMap::new(),
+                paper_columns: Vec::new(),
+                scroll_offset: 0.0,
+                monocle_order: Vec::new(),
+                monocle_active: None,
+            },
+            unfit_windows: Vec::new(),
+            tiling_area: Rect::new(0.0, 0.0, 0.0, 0.0),
+            inner_gap: 0.0,
+        };
+
+        for layout in BuiltinLayout::all() {
+            manager.layouts.push(Box::new(layout));
+        }
+        for template in &config.templates {
+            let result = TemplateLayout::from_config(template)
+                .and_then(|layout| manager.register(Box::new(layout)));
+            if let Err(e) = result {
+                error!(
+                    "Failed to register template layout {}: {}",
+                    template.name, e
+                );
+            }
+        }
+        if !manager.set_default_layout(&config.default_layout) {
+            // Plugin layouts are registered later, and checked once they are
+            warn!(
+                "default_layout '{}' is not registered, starting with {}",
+                config.default_layout,
+                manager.get_current_layout()
+            );
+        }
+        manager
+    }
+
+    /// Add a layout to the end of the cycle. Its name and aliases must not
+    /// clash with a layout already registered.
+    pub fn register(&mut self, layout: Box<dyn Layout>) -> Result<()> {
+        let names = std::iter::once(layout.name()).chain(layout.aliases().iter().copied());
+        for name in names {
+            if self.find_layout(name).is_some() {
+                return Err(anyhow::anyhow!("Layout '{}' is already registered", name));
+            }
+        }
+
+        self.layouts.push(layout);
+        Ok(())
+    }
+
+    /// Check the layout names in the config against the registered layouts.
+    /// Plugins can add layouts, so this runs once they have registered.
+    pub fn validate_config(&self, config: &LayoutConfig) -> Result<()> {
+        if self.find_layout(&config.default_layout).is_none() {
+            return Err(anyhow::anyhow!(
+                "default_layout must be one of {:?}, got '{}'",
+                self.layout_names(),
+                config.default_layout
+            ));
+        }
+
+        for name in &config.cycle {
+            if self.find_layout(name).is_none() {
+                return Err(anyhow::anyhow!(
+                    "cycle entries must be one of {:?}, got '{}'",
+                    self.layout_names(),
+                    name
+                ));
+            }
+        }
+
+        for (i, rule) in config.rules.iter().enumerate() {
+            if self.find_layout(&rule.layout).is_none() {
+                return Err(anyhow::anyhow!(
+                    "rule {}: layout must be one of {:?}, got '{}'",
+                    i + 1,
+                    self.layout_names(),
+                    rule.layout
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Switch to the layout for `workspace`: that of the first matching
+    /// rule, unless its layout was changed by hand, otherwise the one picked
+    /// by hand there, otherwise `default_layout`. Returns whether the layout
+    /// changed.
+    pub fn apply_rules(&mut self, workspace: u32, window_count: usize, screen_rect: &Rect) -> bool {
+        let rule_layout = if self.manual_workspaces.contains(&workspace) {
+            None
+        } else {
+            self.rules
+                .iter()
+                .find(|rule| rule.matches(workspace, window_count, screen_rect))
+                .and_then(|rule| self.find_layout(&rule.layout))
+        };
+        let index = rule_layout
+            .or_else(|| self.workspace_layouts.get(&workspace).copied())
+            .unwrap_or(self.default_layout);
+        if index == self.current_layout {
+            return false;
+        }
+        self.current_layout = index;
+        true
+    }
+
+    /// Keep the current layout on `workspace`, after it was picked there by
+    /// hand, and stop choosing layouts automatically. Returns false if the
+    /// rules were already off.
+    pub fn disable_rules(&mut self, workspace: u32) -> bool {
+        self.workspace_layouts
+            .insert(workspace, self.current_layout);
+        !self.rules.is_empty() && self.manual_workspaces.insert(workspace)
+    }
+
+    /// Let the rules choose the layout on `workspace` again. Returns false
+    /// if they were not turned off.
+    pub fn enable_rules(&mut self, workspace: u32) -> bool {
+        self.manual_workspaces.remove(&workspace)
+    }
+
+    /// Whether rules currently choose the layout on `workspace`.
+    pub fn rules_active(&self, workspace: u32) -> bool {
+        !self.rules.is_empty() && !self.manual_workspaces.contains(&workspace)
+    }
+
+    /// Check the layouts named by `layout:<name>` hotkey actions.
+    pub fn validate_bindings(&self, config: &HotkeyConfig) -> Result<()> {
+        for (key_combo, action) in &config.bindings {
+            if let Some(name) = action.strip_prefix("layout:") {
+                if self.find_layout(name).is_none() {
+                    return Err(anyhow::anyhow!(
+                        "Binding '{}' names unknown layout '{}', expected one of {:?}",
+                        key_combo,
+                        name,
+                        self.layout_names()
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Names of the registered layouts in registration order.
+    pub fn layout_names(&self) -> Vec<&str> {
+        self.layouts.iter().map(|layout| layout.name()).collect()
+    }
+
+    /// Registry positions of the layouts in the cycle.
+    fn cycle_indices(&self) -> Vec<usize> {
+        if self.cycle.is_empty() {
+            return (0..self.layouts.len()).collect();
+        }
+        self.cycle
+            .iter()
+            .filter_map(|name| self.find_layout(name))
+            .collect()
+    }
+
+    /// Names of the layouts `layout_next` and `layout_prev` step through.
+    pub fn cycle_names(&self) -> Vec<&str> {
+        self.cycle_indices()
+            .into_iter()
+            .map(|index| self.layouts[index].name())
+            .collect()
+    }
+
+    fn find_layout(&self, name: &str) -> Option<usize> {
+        let name = name.to_lowercase();
+        self.layouts.iter().position(|layout| {
+            layout.name() == name || layout.aliases().iter().any(|alias| *alias == name)
+        })
+    }
+
+    fn is_current(&self, name: &str) -> bool {
+        self.layouts
+            .get(self.current_layout)
+            .is_some_and(|layout| layout.name() == name)
+    }
+
+    /// Tile `windows` on the screen. Padding and the outer gap come off the
+    /// screen first; each layout then insets its tiles by half the inner
+    /// gap, so neighbours end up `inner_gap` apart and edge windows
+    /// `outer_gap` from the padded screen edge.
+    pub fn compute_layout(
+        &mut self,
+        windows: &[&Window],
+        screen_rect: Rect,
+        general_config: &GeneralConfig,
+    ) -> HashMap<WindowId, Rect> {
+        for window in windows {
+            self.track_window(window);
+        }
+        let ordered = self.in_window_order(windows);
+        let windows = &ordered[..];
+
+        let (screen_rect, gap) = self.tiling_area(windows.len(), screen_rect, general_config);
+        self.tiling_area = screen_rect;
+        self.inner_gap = gap;
+
+        // The layout borrows the manager while it runs, so lift it out of
+        // the registry for the duration
+        let index = self.current_layout;
+        let running = RunningLayout {
+            name: self.layouts[index].name().to_string(),
+            tiles: self.layouts[index].tiles(),
+            follows_focus: self.layouts[index].follows_focus(),
+        };
+        let mut layout = std::mem::replace(&mut self.layouts[index], Box::new(running));
+        let result = layout.compute(self, windows, screen_rect, gap);
+        let (name, mut tiles) = (layout.name().to_string(), layout.tiles());
+        self.layouts[index] = layout;
+
+        let mut rects = match result {
+            Ok(rects) => rects,
+            Err(e) => {
+                error!("Layout {} failed, falling back to BSP: {}", name, e);
+                tiles = true;
+                self.compute_bsp_layout(windows, screen_rect, gap)
+            }
+        };
+
+        self.unfit_windows.clear();
+        if tiles {
+            self.apply_size_hints(windows, &mut rects);
+        }
+        rects
+    }
+
+    /// The area tiles are laid out in and the inner gap between them. With
+    /// `smart_gaps` a lone tiled window, or monocle's single visible one,
+    /// gets neither gap; padding always applies.
+    fn tiling_area(
+        &self,
+        window_count: usize,
+        screen_rect: Rect,
+        general_config: &GeneralConfig,
+    ) -> (Rect, f64) {
+        let single = window_count == 1 || self.is_current(MONOCLE_LAYOUT);
+        let (inner_gap, outer_gap) = if general_config.smart_gaps && single {
+            (0.0, 0.0)
+        } else {
+            (general_config.inner_gap, general_config.outer_gap)
+        };
+
+        // Tiles add half the inner gap back at the edges
+        let inset = outer_gap - inner_gap / 2.0;
+        let padding = &general_config.padding;
+        let area = Rect::new(
+            screen_rect.x + padding.left + inset,
+            screen_rect.y + padding.top + inset,
+            screen_rect.width - padding.left - padding.right - 2.0 * inset,
+            screen_rect.height - padding.top - padding.bottom - 2.0 * inset,
+        );
+        (area, inner_gap)
+    }
+
+    /// Last pass over a tiled layout: windows with a maximum size smaller
+    /// than their tile are shrunk and centred in it, and windows whose tile
+    /// is still below their minimum size are recorded in `unfit_windows`.
+    fn apply_size_hints(&mut self, windows: &[&Window], rects: &mut HashMap<WindowId, Rect>) {
+        for window in windows {
+            let hints = window.size_hints;
+            if hints.is_empty() {
+                continue;
+            }
+            let Some(rect) = rects.get_mut(&window.id) else {
+                continue;
+            };
+
+            if !hints.fits(rect) {
+                self.unfit_windows.push(window.id);
+            }
+
+            let (max_width, max_height) = hints.max_size();
+            let width = rect.width.min(max_width);
+            let height = rect.height.min(max_height);
+            *rect = Rect::new(
+                rect.x + (rect.width - width) / 2.0,
+                rect.y + (rect.height - height) / 2.0,
+                width,
+                height,
+            );
+        }
+    }
+
+    /// Windows the last computed layout could not give their minimum size.
+    pub fn unfit_windows(&self) -> &[WindowId] {
+        &self.unfit_windows
+    }
+
+    fn compute_bsp_layout(
+        &mut self,
+        windows: &[&Window],
+        screen_rect: Rect,
+        gap: f64,
+    ) -> HashMap<WindowId, Rect> {
+        if windows.is_empty() {
+            self.bsp_root = None;
+            return HashMap::new();
+        }
+
+        self.sync_bsp_tree(windows, screen_rect);
+
+        let root = match self.bsp_root {
+            Some(ref root) => root,
+            None => return HashMap::new(),
+        };
+        let hints: HashMap<WindowId, SizeHints> =
+            windows.iter().map(|w| (w.id, w.size_hints)).collect();
+        let mut rects = root.collect_window_rects(&hints, gap, self.title_bar_height);
+
+        // Park hidden group members in the bottom-right corner. macOS keeps
+        // at least part of a window on screen, so leave a 1px sliver there
+        // rather than asking for a position it would clamp.
+        for window_id in root.hidden_window_ids() {
+            if let Some(window) = windows.iter().find(|w| w.id == window_id) {
+                rects.insert(
+                    window_id,
+                    Rect::new(
+                        screen_rect.x + screen_rect.width - 1.0,
+                        screen_rect.y + screen_rect.height - 1.0,
+                        window.rect.width,
+                        window.rect.height,
+                    ),
+                );
+            }
+        }
+
+        rects
+    }
+
+    /// Bring the persistent BSP tree in line with the current window set.
+    /// Closed windows are removed and new ones inserted; every other node
+    /// keeps its ratio, orientation and position.
+    fn sync_bsp_tree(&mut self, windows: &[&Window], screen_rect: Rect) {
+        let stale: Vec<WindowId> = match self.bsp_root {
+            Some(ref root) => root
+                .window_ids()
+                .into_iter()
+                .filter(|id| !windows.iter().any(|w| w.id == *id))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        for window_id in stale {
+            let left_in_group = self
+                .bsp_root
+                .as_mut()
+                .is_some_and(|root| root.remove_from_group(window_id));
+            if left_in_group {
+                continue;
+            }
+
+            let root_is_window = self
+                .bsp_root
+                .as_ref()
+                .is_some_and(|root| root.window_id == Some(window_id));
+
+            if root_is_window {
+                self.bsp_root = None;
+            } else if let Some(ref mut root) = self.bsp_root {
+                root.remove_window(window_id);
+            }
+        }
+
+        let focused_id = windows
+            .iter()
+            .find(|w| w.is_focused)
+            .map(|w| w.id)
+            .filter(|id| {
+                self.bsp_root
+                    .as_ref()
+                    .is_some_and(|root| root.contains_window(*id))
+            });
+
+        // Focusing a hidden group member (e.g. with cmd-tab) brings it forward
+        if let (Some(root), Some(focused_id)) = (self.bsp_root.as_mut(), focused_id) {
+            root.activate(focused_id);
+            self.last_focused = Some(focused_id);
+        }
+
+        let target_id = focused_id.or(self.last_focused);
+        for window in windows {
+            match self.bsp_root {
+                Some(ref root) if root.contains_window(window.id) => {}
+                Some(_) => self.insert_bsp_window(window.id, target_id),
+                None => self.bsp_root = Some(BSPNode::new_leaf(window.id, screen_rect)),
+            }
+        }
+
+        if let Some(ref mut root) = self.bsp_root {
+            if root.rect != screen_rect {
+                root.update_rects(screen_rect);
+            }
+        }
+    }
+
+    /// Master/stack tiling in the style of dwm's tile and xmonad's Tall: the
+    /// first `nmaster` windows share the master area on `master_position`,
+    /// the rest share the stack. Within each area windows are sized by their
+    /// weight.
+    fn compute_stack_layout(
+        &self,
+        windows: &[&Window],
+        screen_rect: Rect,
+        gap: f64,
+    ) -> HashMap<WindowId, Rect> {
+        let mut rects = HashMap::new();
+
+        if windows.is_empty() {
+            return rects;
+        }
+
+        let (masters, stack) = windows.split_at(self.nmaster.min(windows.len()));
+        let horizontal = self.master_position.is_horizontal();
+        let extent = if horizontal {
+            screen_rect.width
+        } else {
+            screen_rect.height
+        };
+        let (master_extent, stack_extent) = if stack.is_empty() {
+            (extent, 0.0)
+        } else if masters.is_empty() {
+            (0.0, extent)
+        } else {
+            let (master_min, master_max) = area_bounds(masters, horizontal, gap);
+            let (stack_min, stack_max) = area_bounds(stack, horizontal, gap);
+            let extents = distribute(
+                extent,
+                &[
+                    (self.split_ratio, master_min, master_max),
+                    (1.0 - self.split_ratio, stack_min, stack_max),
+                ],
+            );
+            (extents[0], extents[1])
+        };
+
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = screen_rect;
+        let (master_area, stack_area) = match self.master_position {
+            MasterPosition::Left => (
+                Rect::new(x, y, master_extent, height),
+                Rect::new(x + master_extent, y, stack_extent, height),
+            ),
+            MasterPosition::Right => (
+                Rect::new(x + stack_extent, y, master_extent, height),
+                Rect::new(x, y, stack_extent, height),
+            ),
+            MasterPosition::Top => (
+                Rect::new(x, y, width, master_extent),
+                Rect::new(x, y + master_extent, width, stack_extent),
+            ),
+            MasterPosition::Bottom => (
+                Rect::new(x, y + stack_extent, width, master_extent),
+                Rect::new(x, y, width, stack_extent),
+            ),
+        };
+
+        // Side-by-side areas stack their windows vertically and vice versa
+        self.split_weighted(masters, master_area, horizontal, gap, &mut rects);
+        self.split_weighted(stack, stack_area, horizontal, gap, &mut rects);
+
+        rects
+    }
+
+    /// Masters in a centre column `split_ratio` wide, stack windows
+    /// alternating into the right and left columns. With a single stack
+    /// window there is nothing to balance, so the master moves to the left
+    /// and the stack window takes the right.
+    fn compute_centered_master_layout(
+        &self,
+        windows: &[&Window],
+        screen_rect: Rect,
+        gap: f64,
+    ) -> HashMap<WindowId, Rect> {
+        let mut rects = HashMap::new();
+
+        if windows.is_empty() {
+            return rects;
+        }
+
+        let (masters, stack) = windows.split_at(self.nmaster.min(windows.len()));
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = screen_rect;
+
+        if masters.is_empty() || stack.is_empty() {
+            self.split_weighted(windows, screen_rect, true, gap, &mut rects);
+            return rects;
+        }
+
+        let (master_min, master_max) = area_bounds(masters, true, gap);
+
+        if stack.len() == 1 {
+            let (stack_min, stack_max) = area_bounds(stack, true, gap);
+            let widths = distribute(
+                width,
+                &[
+                    (self.split_ratio, master_min, master_max),
+                    (1.0 - self.split_ratio, stack_min, stack_max),
+                ],
+            );
+            let master_area = Rect::new(x, y, widths[0], height);
+            let stack_area = Rect::new(x + widths[0], y, widths[1], height);
+            self.split_weighted(masters, master_area, true, gap, &mut rects);
+            self.split_weighted(stack, stack_area, true, gap, &mut rects);
+            return rects;
+        }
+
+        let right: Vec<&Window> = stack.iter().step_by(2).copied().collect();
+        let left: Vec<&Window> = stack.iter().skip(1).step_by(2).copied().collect();
+
+        let side_ratio = (1.0 - self.split_ratio) / 2.0;
+        let (left_min, left_max) = area_bounds(&left, true, gap);
+        let (right_min, right_max) = area_bounds(&right, true, gap);
+        let widths = distribute(
+            width,
+            &[
+                (side_ratio, left_min, left_max),
+                (self.split_ratio, master_min, master_max),
+                (side_ratio, right_min, right_max),
+            ],
+        );
+        let left_area = Rect::new(x, y, widths[0], height);
+        let master_area = Rect::new(x + widths[0], y, widths[1], height);
+        let right_area = Rect::new(x + widths[0] + widths[1], y, widths[2], height);
+
+        self.split_weighted(masters, master_area, true, gap, &mut rects);
+        self.split_weighted(&left, left_area, true, gap, &mut rects);
+        self.split_weighted(&right, right_area, true, gap, &mut rects);
+
+        rects
+    }
+
+    /// Masters in the left column, the stack split between the middle and
+    /// right columns. Column widths follow `three_column_ratios`, shared out
+    /// among the columns that actually hold windows.
+    fn compute_three_column_layout(
+        &self,
+        windows: &[&Window],
+        screen_rect: Rect,
+        gap: f64,
+    ) -> HashMap<WindowId, Rect> {
+        let mut rects = HashMap::new();
+
+        if windows.is_empty() {
+            return rects;
+        }
+
+        let (masters, stack) = windows.split_at(self.nmaster.min(windows.len()));
+        let (middle, right) = stack.split_at(stack.len().div_ceil(2));
+        let columns: Vec<(&[&Window], f64)> = [masters, middle, right]
+            .into_iter()
+            .zip(self.three_column_ratios)
+            .filter(|(column, _)| !column.is_empty())
+            .collect();
+
+        let bounds: Vec<(f64, f64, f64)> = columns
+            .iter()
+            .map(|(column, ratio)| {
+                let (min, max) = area_bounds(column, true, gap);
+                (*ratio, min, max)
+            })
+            .collect();
+        let widths = distribute(screen_rect.width, &bounds);
+        let mut offset = screen_rect.x;
+
+        for ((column, _), column_width) in columns.into_iter().zip(widths) {
+            let area = Rect::new(offset, screen_rect.y, column_width, screen_rect.height);
+            self.split_weighted(column, area, true, gap, &mut rects);
+            offset += column_width;
+        }
+
+        rects
+    }
+
+    /// Divide `area` between `windows` in proportion to their weights, top
+    /// to bottom when `vertical`, otherwise left to right.
+    fn split_weighted(
+        &self,
+        windows: &[&Window],
+        area: Rect,
+        vertical: bool,
+        gap: f64,
+        rects: &mut HashMap<WindowId, Rect>,
+    ) {
+        let bounds: Vec<(f64, f64, f64)> = windows
+            .iter()
+            .map(|window| {
+                let (min, max) = window_bounds(window, !vertical, gap);
+                (self.get_window_weight(window.id), min, max)
+            })
+            .collect();
+        let extent = if vertical { area.height } else { area.width };
+        let mut offset = 0.0;
+
+        for (window, size) in windows.iter().zip(distribute(extent, &bounds)) {
+            let tile = if vertical {
+                Rect::new(area.x, area.y + offset, area.width, size)
+            } else {
+                Rect::new(area.x + offset, area.y, size, area.height)
+            };
+            offset += size;
+
+            rects.insert(
+                window.id,
+                Rect::new(
+                    tile.x + gap / 2.0,
+                    tile.y + gap / 2.0,
+                    tile.width - gap,
+                    tile.height - gap,
+                ),
+            );
+        }
+    }
+
+    fn compute_float_layout(
+        &self,
+        windows: &[&Window],
+        _screen_rect: Rect,
+        _gap: f64,
+    ) -> HashMap<WindowId, Rect> {
+        windows.iter().map(|w| (w.id, w.rect.clone())).collect()
+    }
+
+    fn compute_grid_layout(
+        &self,
+        windows: &[&Window],
+        screen_rect: Rect,
+        gap: f64,
+    ) -> HashMap<WindowId, Rect> {
+        let mut rects = HashMap::new();
+
+        if windows.is_empty() {
+            return rects;
+        }
+
+        let window_count = windows.len();
+        let (rows, cols) = self.grid_dimensions(window_count, &screen_rect);
+        let column_major = self.grid_order == GridOrder::ColumnMajor;
+
+        // The last row (column in column-major order) may be incomplete
+        let line_len = if column_major { rows } else { cols };
+        let last_line = (window_count - 1) / line_len;
+        let last_line_len = window_count - last_line * line_len;
+
+        for (i, window) in windows.iter().enumerate() {
+            let (line, pos) = (i / line_len, i % line_len);
+            let cells_in_line = if self.grid_stretch_last && line == last_line {
+                last_line_len
+            } else {
+                line_len
+            };
+
+            let (row, col, row_count, col_count) = if column_major {
+                (pos, line, cells_in_line, cols)
+            } else {
+                (line, pos, rows, cells_in_line)
+            };
+
+            let cell_width = screen_rect.width / col_count as f64;
+            let cell_height = screen_rect.height / row_count as f64;
+            let x = screen_rect.x + col as f64 * cell_width;
+            let y = screen_rect.y + row as f64 * cell_height;
+
+            let rect = Rect::new(
+                x + gap / 2.0,
+                y + gap / 2.0,
+                cell_width - gap,
+                cell_height - gap,
+            );
+            rects.insert(window.id, rect);
+        }
+
+        rects
+    }
+
+    /// Rows and columns for `window_count` cells. A fixed `grid_rows` or
+    /// `grid_columns` wins; otherwise the grid is as square as possible with
+    /// the longer side along the screen's longer edge.
+    ///
+    /// Cells fill one row (column in column-major order) at a time, so the
+    /// other dimension is however many lines that takes. A fixed count in
+    /// that dimension is a maximum: `grid_rows = 3` with 4 windows gives two
+    /// rows of two rather than an empty third row.
+    fn grid_dimensions(&self, window_count: usize, screen_rect: &Rect) -> (usize, usize) {
+        let (rows, cols) = if self.grid_columns > 0 {
+            (window_count.div_ceil(self.grid_columns), self.grid_columns)
+        } else if self.grid_rows > 0 {
+            (self.grid_rows, window_count.div_ceil(self.grid_rows))
+        } else {
+            let long_side = (window_count as f64).sqrt().ceil() as usize;
+            let short_side = window_count.div_ceil(long_side);
+            if screen_rect.height > screen_rect.width {
+                (long_side, short_side)
+            } else {
+                (short_side, long_side)
+            }
+        };
+
+        if self.grid_order == GridOrder::ColumnMajor {
+            (rows, window_count.div_ceil(rows))
+        } else {
+            (window_count.div_ceil(cols), cols)
+        }
+    }
+
+    fn compute_spiral_layout(
+        &self,
+        windows: &[&Window],
+        screen_rect: Rect,
+        gap: f64,
+    ) -> HashMap<WindowId, Rect> {
+        self.compute_fibonacci_layout(windows, screen_rect, gap, 4)
+    }
+
+    fn compute_dwindle_layout(
+        &self,
+        windows: &[&Window],
+        screen_rect: Rect,
+        gap: f64,
+    ) -> HashMap<WindowId, Rect> {
+        self.compute_fibonacci_layout(windows, screen_rect, gap, 2)
+    }
+
+    /// Each window takes `split_ratio` of the space left by the previous one,
+    /// cutting in turn from the left, top, right and bottom. Spiral uses all
+    /// four directions (`turns == 4`), dwindle only the first two.
+    fn compute_fibonacci_layout(
+        &self,
+        windows: &[&Window],
+        screen_rect: Rect,
+        gap: f64,
+        turns: usize,
+    ) -> HashMap<WindowId, Rect> {
+        let mut rects = HashMap::new();
+
+        if windows.is_empty() {
+            return rects;
+        }
+
+        let half_gap = gap / 2.0;
+        let mut remaining = screen_rect;
+
+        for (i, window) in windows.iter().enumerate() {
+            let tile = if i == windows.len() - 1 {
+                remaining
+            } else {
+                let width = remaining.width * self.split_ratio;
+                let height = remaining.height * self.split_ratio;
+                let (tile, rest) = match i % turns {
+                    // Window on the left, the rest continues to the right
+                    0 => (
+                        Rect::new(remaining.x, remaining.y, width, remaining.height),
+                        Rect::new(
+                            remaining.x + width,
+                            remaining.y,
+                            remaining.width - width,
+                            remaining.height,
+                        ),
+                    ),
+                    // Window on top, the rest continues downwards
+                    1 => (
+                        Rect::new(remaining.x, remaining.y, remaining.width, height),
+                        Rect::new(
+                            remaining.x,
+                            remaining.y + height,
+                            remaining.width,
+                            remaining.height - height,
+                        ),
+                    ),
+                    // Window on the right, the rest continues to the left
+                    2 => (
+                        Rect::new(
+                            remaining.x + remaining.width - width,
+                            remaining.y,
+                            width,
+                            remaining.height,
+                        ),
+                        Rect::new(
+                            remaining.x,
+                            remaining.y,
+                            remaining.width - width,
+                            remaining.height,
+                        ),
+                    ),
+                    // Window at the bottom, the rest continues upwards
+                    _ => (
+                        Rect::new(
+                            remaining.x,
+                            remaining.y + remaining.height - height,
+                            remaining.width,
+                            height,
+                        ),
+                        Rect::new(
+                            remaining.x,
+                            remaining.y,
+                            remaining.width,
+                            remaining.height - height,
+                        ),
+                    ),
+                };
+                remaining = rest;
+                tile
+            };
+
+            rects.insert(
+                window.id,
+                Rect::new(
+                    tile.x + half_gap,
+                    tile.y + half_gap,
+                    tile.width - gap,
+                    tile.height - gap,
+                ),
+            );
+        }
+
+        rects
+    }
+
+    /// One column per window on a horizontal strip that may be wider than
+    /// the screen. The view scrolls just far enough to keep the focused
+    /// column on screen; everything else keeps its place on the strip, so
+    /// columns outside the viewport get off-screen rects.
+    fn compute_paper_layout(
+        &mut self,
+        windows: &[&Window],
+        screen_rect: Rect,
+        gap: f64,
+    ) -> HashMap<WindowId, Rect> {
+        let mut rects = HashMap::new();
+
+        let focused_id = windows.iter().find(|w| w.is_focused).map(|w| w.id);
+        self.sync_paper_columns(windows, focused_id);
+
+        if self.paper_columns.is_empty() {
+            self.scroll_offset = 0.0;
+            return rects;
+        }
+
+        let mut columns = Vec::with_capacity(self.paper_columns.len());
+        let mut strip_width = 0.0;
+        for window_id in &self.paper_columns {
+            let width = screen_rect.width * self.get_column_width(*window_id);
+            columns.push((*window_id, strip_width, width));
+            strip_width += width;
+        }
+
+        if let Some((_, start, width)) = columns
+            .iter()
+            .find(|(window_id, _, _)| Some(*window_id) == focused_id)
+        {
+            if *start < self.scroll_offset {
+                self.scroll_offset = *start;
+            } else if start + width > self.scroll_offset + screen_rect.width {
+                self.scroll_offset = start + width - screen_rect.width;
+            }
+        }
+        self.scroll_offset = self
+            .scroll_offset
+            .clamp(0.0, (strip_width - screen_rect.width).max(0.0));
+
+        let half_gap = gap / 2.0;
+        for (window_id, start, width) in columns {
+            rects.insert(
+                window_id,
+                Rect::new(
+                    screen_rect.x + start - self.scroll_offset + half_gap,
+                    screen_rect.y + half_gap,
+                    width - gap,
+                    screen_rect.height - gap,
+                ),
+            );
+        }
+
+        rects
+    }
+
+    /// Drop columns of windows that are gone and open a column for each new
+    /// window right after the focused one, or at the end of the strip.
+    fn sync_paper_columns(&mut self, windows: &[&Window], focused_id: Option<WindowId>) {
+        self.paper_columns
+            .retain(|id| windows.iter().any(|w| w.id == *id));
+
+        for window in windows {
+            if self.paper_columns.contains(&window.id) {
+                continue;
+            }
+
+            let index = focused_id
+                .filter(|id| *id != window.id)
+                .and_then(|id| self.paper_columns.iter().position(|c| *c == id))
+                .map(|index| index + 1)
+                .unwrap_or(self.paper_columns.len());
+            self.paper_columns.insert(index, window.id);
+        }
+    }
+
+    fn compute_column_layout(
+        &self,
+        windows: &[&Window],
+        screen_rect: Rect,
+        gap: f64,
+    ) -> HashMap<WindowId, Rect> {
+        let mut rects = HashMap::new();
+
+        if windows.is_empty() {
+            return rects;
+        }
+
+        let bounds: Vec<(f64, f64, f64)> = windows
+            .iter()
+            .map(|window| {
+                let (min, max) = window_bounds(window, true, gap);
+                (1.0, min, max)
+            })
+            .collect();
+        let mut x = screen_rect.x;
+
+        for (window, width) in windows.iter().zip(distribute(screen_rect.width, &bounds)) {
+            let rect = Rect::new(
+                x + gap / 2.0,
+                screen_rect.y + gap / 2.0,
+                width - gap,
+                screen_rect.height - gap,
+            );
+            rects.insert(window.id, rect);
+            x += width;
+        }
+
+        rects
+    }
+
+    fn compute_monocle_layout(
+        &mut self,
+        windows: &[&Window],
+        screen_rect: Rect,
+        gap: f64,
+    ) -> HashMap<WindowId, Rect> {
+        let mut rects = HashMap::new();
+
+        self.sync_monocle_order(windows);
+        if windows.is_empty() {
+            return rects;
+        }
+
+        // In monocle mode, all windows are fullscreen (only focused one is visible)
+        let fullscreen_rect = Rect::new(
+            screen_rect.x + gap / 2.0,
+            screen_rect.y + gap / 2.0,
+            screen_rect.width - gap,
+            screen_rect.height - gap,
+        );
+
+        for window in windows {
+            rects.insert(window.id, fullscreen_rect.clone());
+        }
+
+        rects
+    }
+
+    /// Keep the monocle window list in opening order and pick the window to
+    /// show: the focused one if any, otherwise the last one shown, otherwise
+    /// the first in the list.
+    fn sync_monocle_order(&mut self, windows: &[&Window]) {
+        self.monocle_order
+            .retain(|id| windows.iter().any(|w| w.id == *id));
+        for window in windows {
+            if !self.monocle_order.contains(&window.id) {
+                self.monocle_order.push(window.id);
+            }
+        }
+
+        let focused_id = windows.iter().find(|w| w.is_focused).map(|w| w.id);
+        self.monocle_active = focused_id
+            .or(self
+                .monocle_active
+                .filter(|id| self.monocle_order.contains(id)))
+            .or(self.monocle_order.first().copied());
+    }
+
+    /// Step to the next or previous window of the monocle list, wrapping
+    /// around. Returns the window to show, or `None` outside monocle.
+    pub fn cycle_monocle(&mut self, forward: bool) -> Option<WindowId> {
+        if !self.is_current(MONOCLE_LAYOUT) || self.monocle_order.is_empty() {
+            return None;
+        }
+
+        let count = self.monocle_order.len();
+        let next = match self
+            .monocle_active
+            .and_then(|active| self.monocle_order.iter().position(|id| *id == active))
+        {
+            Some(index) if forward => (index + 1) % count,
+            Some(index) => (index + count - 1) % count,
+            None => 0,
+        };
+
+        self.monocle_active = Some(self.monocle_order[next]);
+        self.monocle_active
+    }
+
+    /// One-based position of the shown window in the monocle list and the
+    /// list's length, e.g. `(2, 5)`.
+    pub fn monocle_position(&self) -> Option<(usize, usize)> {
+        if !self.is_current(MONOCLE_LAYOUT) {
+            return None;
+        }
+
+        let active = self.monocle_active?;
+        let index = self.monocle_order.iter().position(|id| *id == active)?;
+        Some((index + 1, self.monocle_order.len()))
+    }
+
+    /// Window the current layout wants on top of the others, which the
+    /// window manager raises after applying the layout.
+    pub fn raised_window(&self) -> Option<WindowId> {
+        if self.is_current(MONOCLE_LAYOUT) {
+            self.monocle_active
+        } else {
+            None
+        }
+    }
+
+    pub fn toggle_layout(&mut self) {
+        self.next_layout();
+    }
+
+    /// Insert a window into the existing tree: at the preselected spot if
+    /// there is one, otherwise next to the focused leaf (or the last one to
+    /// have focus), and failing both at the bottom-right-most leaf.
+    fn insert_bsp_window(&mut self, window_id: WindowId, focused_id: Option<WindowId>) {
+        let split_ratio = self.split_ratio;
+        let split_mode = self.split_mode;
+        let preselection = self.preselection.take();
+        let root = match self.bsp_root {
+            Some(ref mut root) => root,
+            None => return,
+        };
+
+        if let Some(presel) = preselection {
+            if let Some(leaf) = root.find_leaf_mut(presel.target) {
+                let ratio = presel.ratio.unwrap_or(split_ratio);
+                leaf.split_leaf_towards(window_id, presel.direction, ratio);
+                return;
+            }
+        }
+
+        let target_id = match focused_id.filter(|id| root.contains_window(*id)) {
+            Some(id) => id,
+            None => match root.last_window_id() {
+                Some(id) => id,
+                None => {
+                    root.insert_window(window_id, split_ratio);
+                    return;
+                }
+            },
+        };
+
+        let parent_horizontal = root.find_parent(target_id).map(|p| p.is_horizontal);
+        if let Some(leaf) = root.find_leaf_mut(target_id) {
+            // Windows opened inside a group join it next to the visible one
+            if let Some(ref mut group) = leaf.group {
+                let index = group
+                    .windows
+                    .iter()
+                    .position(|id| *id == target_id)
+                    .map_or(group.windows.len(), |index| index + 1);
+                group.windows.insert(index, window_id);
+                leaf.window_id = Some(window_id);
+                return;
+            }
+
+            let is_horizontal = split_mode.is_horizontal(&leaf.rect, parent_horizontal);
+            leaf.split_leaf(window_id, split_ratio, is_horizontal, false);
+        }
+    }
+
+    /// Add a window to the end of its workspace's order if it is not there
+    /// yet. A window that changed workspace leaves its old order.
+    pub fn track_window(&mut self, window: &Window) {
+        let tracked = self
+            .window_orders
+            .get(&window.workspace_id)
+            .is_some_and(|order| order.contains(&window.id));
+        if tracked {
+            return;
+        }
+
+        for order in self.window_orders.values_mut() {
+            order.retain(|id| *id != window.id);
+        }
+        self.window_orders
+            .entry(window.workspace_id)
+            .or_default()
+            .push(window.id);
+    }
+
+    /// `windows` sorted by their workspace's order, untracked ones last.
+    fn in_window_order<'a>(&self, windows: &[&'a Window]) -> Vec<&'a Window> {
+        let mut ordered = windows.to_vec();
+        ordered.sort_by_key(|window| {
+            self.window_orders
+                .get(&window.workspace_id)
+                .and_then(|order| order.iter().position(|id| *id == window.id))
+                .unwrap_or(usize::MAX)
+        });
+        ordered
+    }
+
+    /// Move a window to the front of its workspace's order, making it the
+    /// master in Stack and the first tile elsewhere.
+    pub fn move_to_first(&mut self, window_id: WindowId) -> bool {
+        self.move_in_order(window_id, true)
+    }
+
+    /// Move a window to the back of its workspace's order.
+    pub fn move_to_last(&mut self, window_id: WindowId) -> bool {
+        self.move_in_order(window_id, false)
+    }
+
+    fn move_in_order(&mut self, window_id: WindowId, first: bool) -> bool {
+        for order in self.window_orders.values_mut() {
+            let Some(position) = order.iter().position(|id| *id == window_id) else {
+                continue;
+            };
+            let target = if first { 0 } else { order.len() - 1 };
+            if position == target {
+                return false;
+            }
+            order.remove(position);
+            if first {
+                order.insert(0, window_id);
+            } else {
+                order.push(window_id);
+            }
+            return true;
+        }
+        false
+    }
+
+    /// Swap a window with the first one in its workspace's order, or with
+    /// the second if it is already first.
+    pub fn swap_main(&mut self, window_id: WindowId) -> bool {
+        let other = self
+            .window_orders
+            .values()
+            .find(|order| order.contains(&window_id))
+            .and_then(|order| match order.first() {
+                Some(first) if *first == window_id => order.get(1),
+                first => first,
+            })
+            .copied();
+        match other {
+            Some(other) => self.swap_windows(window_id, other),
+            None => false,
+        }
+    }
+
+    /// Exchange the places of two windows: in the window order, the Paper
+    /// columns and the monocle list, and in the BSP tree when BSP is the
+    /// current layout.
+    pub fn swap_windows(&mut self, a: WindowId, b: WindowId) -> bool {
+        if a == b {
+            return false;
+        }
+
+        let mut swapped = false;
+        let lists = self
+            .window_orders
+            .values_mut()
+            .chain([&mut self.paper_columns, &mut self.monocle_order]);
+        for list in lists {
+            let positions = (
+                list.iter().position(|id| *id == a),
+                list.iter().position(|id| *id == b),
+            );
+            if let (Some(position_a), Some(position_b)) = positions {
+                list.swap(position_a, position_b);
+                swapped = true;
+            }
+        }
+
+        if !self.is_current(BSP_LAYOUT) {
+            return swapped;
+        }
+        let root = match self.bsp_root.as_mut() {
+            Some(root) => root,
+            None => return swapped,
+        };
+        let (path_a, path_b) = match (root.path_to(a), root.path_to(b)) {
+            (Some(path_a), Some(path_b)) => (path_a, path_b),
+            _ => return swapped,
+        };
+
+        for (path, old, new) in [(path_a, a, b), (path_b, b, a)] {
+            if let Some(leaf) = root.node_at_mut(&path) {
+                leaf.window_id = Some(new);
+                if let Some(ref mut group) = leaf.group {
+                    for id in group.windows.iter_mut().filter(|id| **id == old) {
+                        *id = new;
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Take a window out of the tree and re-insert it on the `direction` side
+    /// of `target`.
+    pub fn move_window_beside(
+        &mut self,
+        window_id: WindowId,
+        target: WindowId,
+        direction: Direction,
+    ) -> bool {
+        if window_id == target || !self.is_current(BSP_LAYOUT) {
+            return false;
+        }
+
+        let split_ratio = self.split_ratio;
+        let root = match self.bsp_root.as_mut() {
+            Some(root) => root,
+            None => return false,
+        };
+        if !root.contains_window(target)
+            || !(root.remove_from_group(window_id) || root.remove_window(window_id))
+        {
+            return false;
+        }
+
+        match root.find_leaf_mut(target) {
+            Some(leaf) => {
+                leaf.split_leaf_towards(window_id, direction, split_ratio);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Turn the tile of `window_id` into a tabbed or stacked group, or with
+    /// `None` break its group back up into ordinary splits. As in i3, a
+    /// window that is not grouped yet pulls every window under its parent
+    /// split into the new group.
+    pub fn set_container_style(
+        &mut self,
+        window_id: WindowId,
+        style: Option<ContainerStyle>,
+    ) -> bool {
+        if !self.is_current(BSP_LAYOUT) {
+            return false;
+        }
+
+        let split_ratio = self.split_ratio;
+        let split_mode = self.split_mode;
+        let root = match self.bsp_root.as_mut() {
+            Some(root) => root,
+            None => return false,
+        };
+        root.activate(window_id);
+        let path = match root.path_to(window_id) {
+            Some(path) => path,
+            None => return false,
+        };
+        let grouped = root.node_at(&path).is_some_and(|n| n.group.is_some());
+
+        match (style, grouped) {
+            (Some(style), true) => {
+                if let Some(group) = root.node_at_mut(&path).and_then(|n| n.group.as_mut()) {
+                    group.style = style;
+                }
+            }
+            (Some(style), false) => {
+                let parent_path = &path[..path.len().saturating_sub(1)];
+                let node = match root.node_at_mut(parent_path) {
+                    Some(node) => node,
+                    None => return false,
+                };
+                let windows = node.window_ids();
+                *node = BSPNode::new_leaf(window_id, node.rect);
+                node.group = Some(WindowGroup { style, windows });
+            }
+            (None, true) => {
+                let leaf = match root.node_at_mut(&path) {
+                    Some(leaf) => leaf,
+                    None => return false,
+                };
+                let group = match leaf.group.take() {
+                    Some(group) => group,
+                    None => return false,
+                };
+
+                // Split the members back out one after another, then even
+                // out the ratios of the resulting subtree
+                let mut previous = window_id;
+                for id in group.windows.into_iter().filter(|id| *id != window_id) {
+                    if let Some(previous_leaf) = leaf.find_leaf_mut(previous) {
+                        let is_horizontal = split_mode.is_horizontal(&previous_leaf.rect, None);
+                        previous_leaf.split_leaf(id, split_ratio, is_horizontal, false);
+                    }
+                    previous = id;
+                }
+                leaf.balance();
+                let rect = leaf.rect;
+                leaf.update_rects(rect);
+            }
+            (None, false) => return false,
+        }
+
+        true
+    }
+
+    /// Show the next or previous member of the group holding `window_id`.
+    /// Returns the window that is now visible.
+    pub fn cycle_container(&mut self, window_id: WindowId, forward: bool) -> Option<WindowId> {
+        self.bsp_root
+            .as_mut()?
+            .find_group_leaf_mut(window_id)?
+            .cycle_group(forward)
+    }
+
+    /// Title bars of every tabbed or stacked group in the BSP tree, for
+    /// drawing tabs and reporting groups over IPC.
+    pub fn title_bars(&self) -> Vec<TitleBar> {
+        let mut bars = Vec::new();
+        if let Some(ref root) = self.bsp_root {
+            root.collect_title_bars(&mut bars, self.inner_gap, self.title_bar_height);
+        }
+        bars
+    }
+
+    /// Whether the window is a group member parked off screen by the BSP
+    /// layout.
+    pub fn is_hidden(&self, window_id: WindowId) -> bool {
+        self.get_current_layout() == BSP_LAYOUT
+            && self
+                .bsp_root
+                .as_ref()
+                .is_some_and(|root| root.hidden_window_ids().contains(&window_id))
+    }
+
+    /// Flip the orientation of the split that contains the given window.
+    pub fn toggle_split(&mut self, window_id: WindowId) -> bool {
+        let parent = match self.bsp_root {
+            Some(ref mut root) => root.find_parent_mut(window_id),
+            None => None,
+        };
+
+        match parent {
+            Some(parent) => {
+                parent.is_horizontal = !parent.is_horizontal;
+                let rect = parent.rect;
+                parent.update_rects(rect);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn rotate_tree(&mut self, rotation: Rotation) -> bool {
+        self.transform_tree(|root| root.rotate(rotation))
+    }
+
+    pub fn mirror_tree(&mut self, axis: MirrorAxis) -> bool {
+        self.transform_tree(|root| root.mirror(axis))
+    }
+
+    pub fn balance_tree(&mut self) -> bool {
+        self.transform_tree(|root| {
+            root.balance();
+        })
     }
 
-    pub fn is_leaf(&self) -> bool {
-        self.left.is_none() && self.right.is_none()
+    fn transform_tree<F: FnOnce(&mut BSPNode)>(&mut self, transform: F) -> bool {
+        match self.bsp_root {
+            Some(ref mut root) => {
+                transform(root);
+                let rect = root.rect;
+                root.update_rects(rect);
+                true
+            }
+            None => false,
+        }
     }
 
-    pub fn insert_window(&mut self, window_id: WindowId, split_ratio: f64) {
-        if self.is_leaf() {
-            if let Some(existing_id) = self.window_id {
-                let left_rect = if self.is_horizontal {
-                    Rect::new(
-                        self.rect.x,
-                        self.rect.y,
-                        self.rect.width * self.split_ratio,
-                        self.rect.height,
-                    )
-                } else {
-                    Rect::new(
-                        self.rect.x,
-                        self.rect.y,
-                        self.rect.width,
-                        self.rect.height * self.split_ratio,
-                    )
+    /// Move the split next to `window_id` by `pixels` in `direction`. In BSP
+    /// this is the nearest ancestor split on that axis; in the master layouts
+    /// it is the master ratio. Pixels are measured against the area of the
+    /// last computed layout. Returns whether anything changed.
+    pub fn resize(
+        &mut self,
+        window_id: Option<WindowId>,
+        direction: Direction,
+        pixels: f64,
+    ) -> bool {
+        let screen_rect = self.tiling_area;
+        let is_horizontal = matches!(direction, Direction::Left | Direction::Right);
+        let sign = match direction {
+            Direction::Left | Direction::Up => -1.0,
+            Direction::Right | Direction::Down => 1.0,
+        };
+
+        match self.get_current_layout() {
+            BSP_LAYOUT => {
+                let (root, window_id) = match (self.bsp_root.as_mut(), window_id) {
+                    (Some(root), Some(window_id)) => (root, window_id),
+                    _ => return false,
+                };
+                let node = match root
+                    .nearest_split_path(window_id, is_horizontal)
+                    .and_then(|path| root.node_at_mut(&path))
+                {
+                    Some(node) => node,
+                    None => return false,
                 };
 
-                let right_rect = if self.is_horizontal {
-                    Rect::new(
-                        self.rect.x + left_rect.width,
-                        self.rect.y,
-                        self.rect.width - left_rect.width,
-                        self.rect.height,
-                    )
+                let extent = if is_horizontal {
+                    node.rect.width
                 } else {
-                    Rect::new(
-                        self.rect.x,
-                        self.rect.y + left_rect.height,
-                        self.rect.width,
-                        self.rect.height - left_rect.height,
-                    )
+                    node.rect.height
                 };
+                if extent <= 0.0 {
+                    return false;
+                }
 
-                self.left = Some(Box::new(BSPNode::new_leaf(existing_id, left_rect)));
-                self.right = Some(Box::new(BSPNode::new_leaf(window_id, right_rect)));
-                self.window_id = None;
-                self.split_ratio = split_ratio;
-            } else {
-                self.window_id = Some(window_id);
+                node.split_ratio = (node.split_ratio + sign * pixels / extent).clamp(0.1, 0.9);
+                let rect = node.rect;
+                node.update_rects(rect);
+                true
             }
-        } else {
-            if let Some(ref mut right) = self.right {
-                right.insert_window(window_id, split_ratio);
+            STACK_LAYOUT => {
+                if is_horizontal != self.master_position.is_horizontal() {
+                    return false;
+                }
+                let extent = if is_horizontal {
+                    screen_rect.width
+                } else {
+                    screen_rect.height
+                };
+                if extent <= 0.0 {
+                    return false;
+                }
+                // Moving the divider towards the stack grows the master area
+                let grows = match self.master_position {
+                    MasterPosition::Left | MasterPosition::Top => sign,
+                    MasterPosition::Right | MasterPosition::Bottom => -sign,
+                };
+                self.adjust_split_ratio(grows * pixels / extent);
+                true
+            }
+            CENTERED_MASTER_LAYOUT => {
+                if !is_horizontal || screen_rect.width <= 0.0 {
+                    return false;
+                }
+                // The master stays centred, so both of its edges move
+                self.adjust_split_ratio(sign * 2.0 * pixels / screen_rect.width);
+                true
+            }
+            SPIRAL_LAYOUT | DWINDLE_LAYOUT => {
+                if !is_horizontal || screen_rect.width <= 0.0 {
+                    return false;
+                }
+                self.adjust_split_ratio(sign * pixels / screen_rect.width);
+                true
             }
+            PAPER_LAYOUT => match window_id {
+                Some(window_id) if is_horizontal && screen_rect.width > 0.0 => {
+                    self.adjust_column_width(window_id, sign * pixels / screen_rect.width);
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
         }
     }
 
-    pub fn collect_window_rects(&self, gap: f64) -> HashMap<WindowId, Rect> {
-        let mut rects = HashMap::new();
-        self.collect_rects_recursive(&mut rects, gap);
-        rects
-    }
+    /// Work out which edges of a tiled window the user dragged from
+    /// `old_rect` to `new_rect` and move the matching splits so the layout
+    /// follows the new size. Pure moves (same size) are ignored. Positions
+    /// are read against the area and gap of the last computed layout.
+    pub fn resize_to_rect(&mut self, window_id: WindowId, old_rect: Rect, new_rect: Rect) -> bool {
+        const TOLERANCE: f64 = 1.0;
 
-    fn collect_rects_recursive(&self, rects: &mut HashMap<WindowId, Rect>, gap: f64) {
-        if let Some(window_id) = self.window_id {
-            let adjusted_rect = Rect::new(
-                self.rect.x + gap / 2.0,
-                self.rect.y + gap / 2.0,
-                self.rect.width - gap,
-                self.rect.height - gap,
-            );
-            rects.insert(window_id, adjusted_rect);
-        } else {
-            if let Some(ref left) = self.left {
-                left.collect_rects_recursive(rects, gap);
-            }
-            if let Some(ref right) = self.right {
-                right.collect_rects_recursive(rects, gap);
-            }
+        if (old_rect.width - new_rect.width).abs() <= TOLERANCE
+            && (old_rect.height - new_rect.height).abs() <= TOLERANCE
+        {
+            return false;
         }
-    }
-}
 
-pub struct LayoutManager {
-    current_layout: LayoutType,
-    bsp_root: Option<BSPNode>,
-    split_ratio: f64,
-}
+        let screen_rect = self.tiling_area;
+        let half_gap = self.inner_gap / 2.0;
+        let old_right = old_rect.x + old_rect.width;
+        let new_right = new_rect.x + new_rect.width;
+        let old_bottom = old_rect.y + old_rect.height;
+        let new_bottom = new_rect.y + new_rect.height;
 
-impl LayoutManager {
-    pub fn new(config: &LayoutConfig) -> Self {
-        Self {
-            current_layout: LayoutType::from_string(&config.default_layout),
-            bsp_root: None,
-            split_ratio: config.split_ratio,
+        // (is_horizontal, window_second, new divider position)
+        let mut moved_edges = Vec::new();
+        if (old_rect.x - new_rect.x).abs() > TOLERANCE {
+            moved_edges.push((true, true, new_rect.x - half_gap));
+        }
+        if (old_right - new_right).abs() > TOLERANCE {
+            moved_edges.push((true, false, new_right + half_gap));
+        }
+        if (old_rect.y - new_rect.y).abs() > TOLERANCE {
+            moved_edges.push((false, true, new_rect.y - half_gap));
+        }
+        if (old_bottom - new_bottom).abs() > TOLERANCE {
+            moved_edges.push((false, false, new_bottom + half_gap));
         }
-    }
 
-    pub fn compute_layout(
-        &mut self,
-        windows: &[&Window],
-        screen_rect: Rect,
-        general_config: &GeneralConfig,
-    ) -> HashMap<WindowId, Rect> {
-        match self.current_layout {
-            LayoutType::BSP => self.compute_bsp_layout(windows, screen_rect, general_config),
-            LayoutType::Stack => self.compute_stack_layout(windows, screen_rect, general_config),
-            LayoutType::Float => self.compute_float_layout(windows, screen_rect, general_config),
-            LayoutType::Grid => self.compute_grid_layout(windows, screen_rect, general_config),
-            LayoutType::Spiral => self.compute_spiral_layout(windows, screen_rect, general_config),
-            LayoutType::Column => self.compute_column_layout(windows, screen_rect, general_config),
-            LayoutType::Monocle => {
-                self.compute_monocle_layout(windows, screen_rect, general_config)
+        match self.get_current_layout() {
+            BSP_LAYOUT => {
+                let root = match self.bsp_root.as_mut() {
+                    Some(root) => root,
+                    None => return false,
+                };
+
+                let mut changed = false;
+                for (is_horizontal, window_second, divider) in moved_edges {
+                    let node = match root
+                        .edge_split_path(window_id, is_horizontal, window_second)
+                        .and_then(|path| root.node_at_mut(&path))
+                    {
+                        Some(node) => node,
+                        None => continue,
+                    };
+
+                    let (start, extent) = if is_horizontal {
+                        (node.rect.x, node.rect.width)
+                    } else {
+                        (node.rect.y, node.rect.height)
+                    };
+                    if extent <= 0.0 {
+                        continue;
+                    }
+
+                    node.split_ratio = ((divider - start) / extent).clamp(0.1, 0.9);
+                    let rect = node.rect;
+                    node.update_rects(rect);
+                    changed = true;
+                }
+                changed
+            }
+            STACK_LAYOUT => {
+                // Only the divider between the master and stack areas is
+                // adjustable, so take the first edge moved along that axis.
+                let horizontal = self.master_position.is_horizontal();
+                let divider = moved_edges
+                    .into_iter()
+                    .find(|(is_horizontal, _, _)| *is_horizontal == horizontal)
+                    .map(|(_, _, divider)| divider);
+                let (start, extent) = if horizontal {
+                    (screen_rect.x, screen_rect.width)
+                } else {
+                    (screen_rect.y, screen_rect.height)
+                };
+
+                match divider {
+                    Some(divider) if extent > 0.0 => {
+                        let ratio = match self.master_position {
+                            MasterPosition::Left | MasterPosition::Top => {
+                                (divider - start) / extent
+                            }
+                            MasterPosition::Right | MasterPosition::Bottom => {
+                                (start + extent - divider) / extent
+                            }
+                        };
+                        self.split_ratio = ratio.clamp(0.1, 0.9);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            SPIRAL_LAYOUT | DWINDLE_LAYOUT => {
+                // Only the divider between the master column and the rest
+                // is adjustable: the master's right edge or a stack window's
+                // left edge.
+                let divider = moved_edges
+                    .into_iter()
+                    .find(|(is_horizontal, _, _)| *is_horizontal)
+                    .map(|(_, _, divider)| divider);
+
+                match divider {
+                    Some(divider) if screen_rect.width > 0.0 => {
+                        self.split_ratio =
+                            ((divider - screen_rect.x) / screen_rect.width).clamp(0.1, 0.9);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            PAPER_LAYOUT => {
+                if (old_rect.width - new_rect.width).abs() <= TOLERANCE || screen_rect.width <= 0.0
+                {
+                    return false;
+                }
+                let width = (new_rect.width + self.inner_gap) / screen_rect.width;
+                self.column_widths
+                    .insert(window_id, width.clamp(MIN_COLUMN_WIDTH, 1.0));
+                true
             }
+            _ => false,
         }
     }
 
-    fn compute_bsp_layout(
-        &mut self,
-        windows: &[&Window],
-        screen_rect: Rect,
-        general_config: &GeneralConfig,
-    ) -> HashMap<WindowId, Rect> {
-        if windows.is_empty() {
-            return HashMap::new();
-        }
+    pub fn inc_master(&mut self) {
+        self.nmaster += 1;
+    }
 
-        let mut root = BSPNode::new_leaf(windows[0].id, screen_rect);
+    pub fn dec_master(&mut self) {
+        self.nmaster = self.nmaster.saturating_sub(1);
+    }
 
-        for window in windows.iter().skip(1) {
-            root.insert_window(window.id, self.split_ratio);
-        }
+    pub fn get_nmaster(&self) -> usize {
+        self.nmaster
+    }
 
-        self.bsp_root = Some(root.clone());
-        root.collect_window_rects(general_config.gap)
+    pub fn set_master_position(&mut self, master_position: MasterPosition) {
+        self.master_position = master_position;
     }
 
-    fn compute_stack_layout(
-        &self,
-        windows: &[&Window],
-        screen_rect: Rect,
-        general_config: &GeneralConfig,
-    ) -> HashMap<WindowId, Rect> {
-        let mut rects = HashMap::new();
+    pub fn get_master_position(&self) -> MasterPosition {
+        self.master_position
+    }
 
-        if windows.is_empty() {
-            return rects;
-        }
+    /// Relative share of its master or stack area a window gets; 1.0 unless
+    /// changed with `adjust_window_weight`.
+    pub fn get_window_weight(&self, window_id: WindowId) -> f64 {
+        self.window_weights.get(&window_id).copied().unwrap_or(1.0)
+    }
 
-        if windows.len() == 1 {
-            let adjusted_rect = Rect::new(
-                screen_rect.x + general_config.gap,
-                screen_rect.y + general_config.gap,
-                screen_rect.width - 2.0 * general_config.gap,
-                screen_rect.height - 2.0 * general_config.gap,
-            );
-            rects.insert(windows[0].id, adjusted_rect);
-            return rects;
-        }
+    pub fn adjust_window_weight(&mut self, window_id: WindowId, delta: f64) -> f64 {
+        let weight = (self.get_window_weight(window_id) + delta).clamp(MIN_WEIGHT, MAX_WEIGHT);
+        self.window_weights.insert(window_id, weight);
+        weight
+    }
 
-        let master_width = screen_rect.width * self.split_ratio;
-        let stack_width = screen_rect.width - master_width;
-        let stack_height = screen_rect.height / (windows.len() - 1) as f64;
+    pub fn reset_window_weight(&mut self, window_id: WindowId) {
+        self.window_weights.remove(&window_id);
+    }
 
-        let master_rect = Rect::new(
-            screen_rect.x + general_config.gap / 2.0,
-            screen_rect.y + general_config.gap / 2.0,
-            master_width - general_config.gap,
-            screen_rect.height - general_config.gap,
-        );
-        rects.insert(windows[0].id, master_rect);
-
-        for (i, window) in windows.iter().skip(1).enumerate() {
-            let stack_rect = Rect::new(
-                screen_rect.x + master_width + general_config.gap / 2.0,
-                screen_rect.y + i as f64 * stack_height + general_config.gap / 2.0,
-                stack_width - general_config.gap,
-                stack_height - general_config.gap,
-            );
-            rects.insert(window.id, stack_rect);
-        }
+    /// Width of a window's Paper column as a fraction of the screen width.
+    pub fn get_column_width(&self, window_id: WindowId) -> f64 {
+        self.column_widths
+            .get(&window_id)
+            .copied()
+            .unwrap_or(self.column_width)
+    }
 
-        rects
+    pub fn adjust_column_width(&mut self, window_id: WindowId, delta: f64) -> f64 {
+        let width = (self.get_column_width(window_id) + delta).clamp(MIN_COLUMN_WIDTH, 1.0);
+        self.column_widths.insert(window_id, width);
+        width
     }
 
-    fn compute_float_layout(
-        &self,
-        windows: &[&Window],
-        _screen_rect: Rect,
-        _general_config: &GeneralConfig,
-    ) -> HashMap<WindowId, Rect> {
-        windows.iter().map(|w| (w.id, w.rect.clone())).collect()
+    /// Move a window's Paper column one place left or right along the strip.
+    /// Returns whether the column moved.
+    pub fn move_column(&mut self, window_id: WindowId, direction: Direction) -> bool {
+        let index = match self.paper_columns.iter().position(|id| *id == window_id) {
+            Some(index) => index,
+            None => return false,
+        };
+        let target = match direction {
+            Direction::Left if index > 0 => index - 1,
+            Direction::Right if index + 1 < self.paper_columns.len() => index + 1,
+            _ => return false,
+        };
+        self.paper_columns.swap(index, target);
+        true
     }
 
-    fn compute_grid_layout(
-        &self,
-        windows: &[&Window],
-        screen_rect: Rect,
-        general_config: &GeneralConfig,
-    ) -> HashMap<WindowId, Rect> {
-        let mut rects = HashMap::new();
+    pub fn get_scroll_offset(&self) -> f64 {
+        self.scroll_offset
+    }
 
-        if windows.is_empty() {
-            return rects;
+    /// Whether the current layout has to be recomputed when focus changes.
+    pub fn follows_focus(&self) -> bool {
+        self.layouts
+            .get(self.current_layout)
+            .is_some_and(|layout| layout.follows_focus())
+    }
+
+    /// Drop per-window state kept for a window that has been closed.
+    pub fn forget_window(&mut self, window_id: WindowId) {
+        if self.last_focused == Some(window_id) {
+            self.last_focused = None;
+        }
+        for order in self.window_orders.values_mut() {
+            order.retain(|id| *id != window_id);
+        }
+        self.window_weights.remove(&window_id);
+        self.column_widths.remove(&window_id);
+        for parked in self.workspaces.values_mut() {
+            parked.window_weights.remove(&window_id);
+            parked.column_widths.remove(&window_id);
         }
+        for layout in &mut self.layouts {
+            layout.forget_window(window_id);
+        }
+    }
 
-        let window_count = windows.len();
-        let cols = (window_count as f64).sqrt().ceil() as usize;
-        let rows = (window_count + cols - 1) / cols;
+    /// Pass a command to the current layout. Returns whether the windows
+    /// need to be re-tiled.
+    pub fn layout_command(&mut self, command: &str, args: &[String]) -> Result<bool> {
+        self.layouts[self.current_layout].command(command, args)
+    }
 
-        let cell_width = (screen_rect.width - general_config.gap * (cols + 1) as f64) / cols as f64;
-        let cell_height =
-            (screen_rect.height - general_config.gap * (rows + 1) as f64) / rows as f64;
+    pub fn set_split_mode(&mut self, split_mode: SplitMode) {
+        self.split_mode = split_mode;
+    }
 
-        for (i, window) in windows.iter().enumerate() {
-            let row = i / cols;
-            let col = i % cols;
+    pub fn get_split_mode(&self) -> SplitMode {
+        self.split_mode
+    }
 
-            let x =
-                screen_rect.x + general_config.gap + col as f64 * (cell_width + general_config.gap);
-            let y = screen_rect.y
-                + general_config.gap
-                + row as f64 * (cell_height + general_config.gap);
+    pub fn preselect(&mut self, target: WindowId, direction: Direction, ratio: Option<f64>) {
+        self.preselection = Some(Preselection {
+            target,
+            direction,
+            ratio: ratio.map(|r| r.clamp(0.1, 0.9)),
+        });
+    }
 
-            let rect = Rect::new(x, y, cell_width, cell_height);
-            rects.insert(window.id, rect);
+    /// Note a focus change, so the next BSP window opens beside this one if
+    /// it is tiled.
+    pub fn window_focused(&mut self, window_id: WindowId) {
+        if self
+            .bsp_root
+            .as_ref()
+            .is_some_and(|root| root.contains_window(window_id))
+        {
+            self.last_focused = Some(window_id);
         }
+    }
 
-        rects
+    pub fn cancel_preselection(&mut self) {
+        self.preselection = None;
     }
 
-    fn compute_spiral_layout(
-        &self,
-        windows: &[&Window],
-        screen_rect: Rect,
-        general_config: &GeneralConfig,
-    ) -> HashMap<WindowId, Rect> {
-        let mut rects = HashMap::new();
+    pub fn get_preselection(&self) -> Option<&Preselection> {
+        self.preselection.as_ref()
+    }
 
-        if windows.is_empty() {
-            return rects;
-        }
+    pub fn bsp_root(&self) -> Option<&BSPNode> {
+        self.bsp_root.as_ref()
+    }
 
-        if windows.len() == 1 {
-            let rect = Rect::new(
-                screen_rect.x + general_config.gap,
-                screen_rect.y + general_config.gap,
-                screen_rect.width - 2.0 * general_config.gap,
-                screen_rect.height - 2.0 * general_config.gap,
-            );
-            rects.insert(windows[0].id, rect);
-            return rects;
-        }
+    pub fn get_current_layout(&self) -> &str {
+        self.layouts[self.current_layout].name()
+    }
 
-        // Spiral layout: first window takes half the screen, others spiral around
-        let main_rect = Rect::new(
-            screen_rect.x + general_config.gap / 2.0,
-            screen_rect.y + general_config.gap / 2.0,
-            screen_rect.width * self.split_ratio - general_config.gap,
-            screen_rect.height - general_config.gap,
-        );
-        rects.insert(windows[0].id, main_rect);
-
-        if windows.len() > 1 {
-            let side_width = screen_rect.width * (1.0 - self.split_ratio);
-            let side_height_per_window = screen_rect.height / (windows.len() - 1) as f64;
-
-            for (i, window) in windows.iter().skip(1).enumerate() {
-                let rect = Rect::new(
-                    screen_rect.x + screen_rect.width * self.split_ratio + general_config.gap / 2.0,
-                    screen_rect.y + i as f64 * side_height_per_window + general_config.gap / 2.0,
-                    side_width - general_config.gap,
-                    side_height_per_window - general_config.gap,
-                );
-                rects.insert(window.id, rect);
-            }
-        }
+    pub fn adjust_split_ratio(&mut self, delta: f64) {
+        self.split_ratio = (self.split_ratio + delta).max(0.1).min(0.9);
+    }
 
-        rects
+    pub fn get_split_ratio(&self) -> f64 {
+        self.split_ratio
     }
 
-    fn compute_column_layout(
-        &self,
-        windows: &[&Window],
-        screen_rect: Rect,
-        general_config: &GeneralConfig,
-    ) -> HashMap<WindowId, Rect> {
-        let mut rects = HashMap::new();
+    pub fn reset_split_ratio(&mut self) {
+        self.split_ratio = 0.5;
+    }
 
-        if windows.is_empty() {
-            return rects;
-        }
+    pub fn next_layout(&mut self) {
+        self.step_layout(true);
+    }
 
-        let window_width = (screen_rect.width - general_config.gap * (windows.len() + 1) as f64)
-            / windows.len() as f64;
+    pub fn previous_layout(&mut self) {
+        self.step_layout(false);
+    }
 
-        for (i, window) in windows.iter().enumerate() {
-            let x =
-                screen_rect.x + general_config.gap + i as f64 * (window_width + general_config.gap);
-            let y = screen_rect.y + general_config.gap;
-            let height = screen_rect.height - 2.0 * general_config.gap;
+    /// Move along the cycle. From a layout outside the cycle, go to its
+    /// first or last entry.
+    fn step_layout(&mut self, forward: bool) {
+        let cycle = self.cycle_indices();
+        let len = cycle.len();
+        if len == 0 {
+            return;
+        }
 
-            let rect = Rect::new(x, y, window_width, height);
-            rects.insert(window.id, rect);
+        let next = match cycle.iter().position(|index| *index == self.current_layout) {
+            Some(position) if forward => (position + 1) % len,
+            Some(position) => (position + len - 1) % len,
+            None if forward => 0,
+            None => len - 1,
+        };
+        self.current_layout = cycle[next];
+    }
+
+    /// Switch to the layout with the given name or alias. Returns false,
+    /// leaving the layout unchanged, if no such layout is registered.
+    pub fn set_layout(&mut self, name: &str) -> bool {
+        match self.find_layout(name) {
+            Some(index) => {
+                self.current_layout = index;
+                true
+            }
+            None => false,
         }
+    }
 
-        rects
+    /// Switch to the named layout and make it the one workspaces without a
+    /// layout of their own use. Returns false if no such layout is
+    /// registered.
+    pub fn set_default_layout(&mut self, name: &str) -> bool {
+        let found = self.set_layout(name);
+        if found {
+            self.default_layout = self.current_layout;
+        }
+        found
     }
 
-    fn compute_monocle_layout(
-        &self,
-        windows: &[&Window],
-        screen_rect: Rect,
-        general_config: &GeneralConfig,
-    ) -> HashMap<WindowId, Rect> {
-        let mut rects = HashMap::new();
+    pub fn current_layout(&self) -> &str {
+        self.get_current_layout()
+    }
 
-        if windows.is_empty() {
-            return rects;
+    /// Park the shown workspace's arrangement and settings and bring back
+    /// those of `workspace`, which starts out with the configured ones the
+    /// first time it is shown.
+    pub fn switch_workspace(&mut self, workspace: u32) {
+        if workspace == self.workspace {
+            return;
         }
 
-        // In monocle mode, all windows are fullscreen (only focused one is visible)
-        let fullscreen_rect = Rect::new(
-            screen_rect.x + general_config.gap,
-            screen_rect.y + general_config.gap,
-            screen_rect.width - 2.0 * general_config.gap,
-            screen_rect.height - 2.0 * general_config.gap,
+        let mut layout = self
+            .workspaces
+            .remove(&workspace)
+            .unwrap_or_else(|| self.new_workspace.clone());
+        std::mem::swap(&mut self.bsp_root, &mut layout.bsp_root);
+        std::mem::swap(&mut self.split_ratio, &mut layout.split_ratio);
+        std::mem::swap(&mut self.preselection, &mut layout.preselection);
+        std::mem::swap(&mut self.last_focused, &mut layout.last_focused);
+        std::mem::swap(&mut self.nmaster, &mut layout.nmaster);
+        std::mem::swap(&mut self.master_position, &mut layout.master_position);
+        std::mem::swap(&mut self.window_weights, &mut layout.window_weights);
+        std::mem::swap(
+            &mut self.three_column_ratios,
+            &mut layout.three_column_ratios,
         );
+        std::mem::swap(&mut self.column_width, &mut layout.column_width);
+        std::mem::swap(&mut self.column_widths, &mut layout.column_widths);
+        std::mem::swap(&mut self.paper_columns, &mut layout.paper_columns);
+        std::mem::swap(&mut self.scroll_offset, &mut layout.scroll_offset);
+        std::mem::swap(&mut self.monocle_order, &mut layout.monocle_order);
+        std::mem::swap(&mut self.monocle_active, &mut layout.monocle_active);
+        self.workspaces.insert(self.workspace, layout);
+        self.workspace = workspace;
+    }
 
-        for window in windows {
-            rects.insert(window.id, fullscreen_rect.clone());
+    pub fn save_state(&self) -> LayoutState {
+        LayoutState {
+            current_layout: self.current_layout,
+            picked_layout: self.workspace_layouts.get(&self.workspace).copied(),
+            rules_off: self.manual_workspaces.contains(&self.workspace),
+            bsp_root: self.bsp_root.clone(),
+            split_ratio: self.split_ratio,
+            nmaster: self.nmaster,
+            master_position: self.master_position,
+            window_weights: self.window_weights.clone(),
+            three_column_ratios: self.three_column_ratios,
+            column_width: self.column_width,
+            column_widths: self.column_widths.clone(),
+            paper_columns: self.paper_columns.clone(),
+            window_order: self
+                .window_orders
+                .get(&self.workspace)
+                .cloned()
+                .unwrap_or_default(),
+            monocle_order: self.monocle_order.clone(),
         }
-
-        rects
     }
 
-    pub fn toggle_layout(&mut self) {
-        self.current_layout = match self.current_layout {
-            LayoutType::BSP => LayoutType::Stack,
-            LayoutType::Stack => LayoutType::Grid,
-            LayoutType::Grid => LayoutType::Spiral,
-            LayoutType::Spiral => LayoutType::Column,
-            LayoutType::Column => LayoutType::Monocle,
-            LayoutType::Monocle => LayoutType::Float,
-            LayoutType::Float => LayoutType::BSP,
+    /// Bring back a saved state of the shown workspace. Windows closed
+    /// since are dropped and new ones inserted the next time the layout is
+    /// computed.
+    fn restore_state(&mut self, state: LayoutState) {
+        self.current_layout = state.current_layout;
+        match state.picked_layout {
+            Some(layout) => self.workspace_layouts.insert(self.workspace, layout),
+            None => self.workspace_layouts.remove(&self.workspace),
         };
+        if state.rules_off {
+            self.manual_workspaces.insert(self.workspace);
+        } else {
+            self.manual_workspaces.remove(&self.workspace);
+        }
+        self.bsp_root = state.bsp_root;
+        self.split_ratio = state.split_ratio;
+        self.nmaster = state.nmaster;
+        self.master_position = state.master_position;
+        self.window_weights = state.window_weights;
+        self.three_column_ratios = state.three_column_ratios;
+        self.column_width = state.column_width;
+        self.column_widths = state.column_widths;
+        self.paper_columns = state.paper_columns;
+        // Windows that moved to another workspace since stay in its order
+        let mut order = state.window_order;
+        order.retain(|id| {
+            !self
+                .window_orders
+                .iter()
+                .any(|(workspace, other)| *workspace != self.workspace && other.contains(id))
+        });
+        self.window_orders.insert(self.workspace, order);
+        self.monocle_order = state.monocle_order;
+        self.preselection = None;
     }
-    
-    pub fn get_current_layout(&self) -> &LayoutType {
-        &self.current_layout
+
+    /// Add `before`, the state ahead of a change, to the shown workspace's
+    /// undo history. Nothing is recorded if the change left the state as it
+    /// was. A new change drops anything that could be redone.
+    pub fn record_history(&mut self, before: LayoutState) {
+        if self.history_size == 0 || before == self.save_state() {
+            return;
+        }
+
+        let history = self.history.entry(self.workspace).or_default();
+        history.undo.push_back(before);
+        while history.undo.len() > self.history_size {
+            history.undo.pop_front();
+        }
+        history.redo.clear();
     }
 
-    pub fn adjust_split_ratio(&mut self, delta: f64) {
-        self.split_ratio = (self.split_ratio + delta).max(0.1).min(0.9);
+    /// Step back to the shown workspace's previous state. Returns false if
+    /// there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let current = self.save_state();
+        let history = self.history.entry(self.workspace).or_default();
+        match history.undo.pop_back() {
+            Some(state) => {
+                history.redo.push(current);
+                self.restore_state(state);
+                true
+            }
+            None => false,
+        }
     }
 
-    pub fn get_split_ratio(&self) -> f64 {
-        self.split_ratio
+    /// Re-apply the last state undone on the shown workspace. Returns false
+    /// if there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let current = self.save_state();
+        let history = self.history.entry(self.workspace).or_default();
+        match history.redo.pop() {
+            Some(state) => {
+                history.undo.push_back(current);
+                self.restore_state(state);
+                true
+            }
+            None => false,
+        }
     }
 
-    pub fn reset_split_ratio(&mut self) {
-        self.split_ratio = 0.5;
+    /// The current layout, its settings and where each window sits, with
+    /// windows identified by application so the arrangement can be rebuilt
+    /// in a later session. Tabbed and stacked groups are saved as their
+    /// visible window.
+    pub fn snapshot(&self, windows: &[&Window]) -> LayoutPreset {
+        let matcher = |window_id: WindowId| {
+            windows
+                .iter()
+                .find(|w| w.id == window_id)
+                .map(|w| WindowMatcher::for_window(w))
+        };
+
+        LayoutPreset {
+            layout: self.get_current_layout().to_string(),
+            split_ratio: Some(self.split_ratio),
+            nmaster: Some(self.nmaster),
+            master_position: Some(self.master_position.name().to_string()),
+            three_column_ratios: Some(self.three_column_ratios.to_vec()),
+            column_width: Some(self.column_width),
+            slots: self
+                .in_window_order(windows)
+                .into_iter()
+                .map(WindowMatcher::for_window)
+                .collect(),
+            bsp: self
+                .bsp_root
+                .as_ref()
+                .and_then(|root| Self::preset_node(root, &matcher)),
+        }
     }
 
-    pub fn next_layout(&mut self) {
-        self.toggle_layout();
+    fn preset_node(
+        node: &BSPNode,
+        matcher: &impl Fn(WindowId) -> Option<WindowMatcher>,
+    ) -> Option<PresetNode> {
+        if let Some(window_id) = node.window_id {
+            return matcher(window_id).map(PresetNode::Window);
+        }
+
+        let first = node
+            .left
+            .as_ref()
+            .and_then(|left| Self::preset_node(left, matcher));
+        let second = node
+            .right
+            .as_ref()
+            .and_then(|right| Self::preset_node(right, matcher));
+        match (first, second) {
+            (Some(first), Some(second)) => Some(PresetNode::Split {
+                horizontal: node.is_horizontal,
+                ratio: node.split_ratio,
+                first: Box::new(first),
+                second: Box::new(second),
+            }),
+            (first, second) => first.or(second),
+        }
     }
 
-    pub fn previous_layout(&mut self) {
-        // Cycle backwards through layouts
-        self.current_layout = match self.current_layout {
-            LayoutType::BSP => LayoutType::Float,
-            LayoutType::Stack => LayoutType::BSP,
-            LayoutType::Grid => LayoutType::Stack,
-            LayoutType::Spiral => LayoutType::Grid,
-            LayoutType::Column => LayoutType::Spiral,
-            LayoutType::Monocle => LayoutType::Column,
-            LayoutType::Float => LayoutType::Monocle,
+    /// Switch to a preset's layout and settings and arrange `windows` the
+    /// way it describes. Each matcher takes the first unclaimed window it
+    /// fits; windows left over are placed after the rest, and parts of the
+    /// BSP tree with no window are dropped.
+    pub fn apply_preset(&mut self, preset: &LayoutPreset, windows: &[&Window]) -> Result<()> {
+        let Some(index) = self.find_layout(&preset.layout) else {
+            return Err(anyhow::anyhow!(
+                "Preset layout '{}' is not registered",
+                preset.layout
+            ));
         };
+        self.current_layout = index;
+
+        if let Some(ratio) = preset.split_ratio {
+            self.split_ratio = ratio;
+        }
+        if let Some(nmaster) = preset.nmaster {
+            self.nmaster = nmaster;
+        }
+        if let Some(position) = preset
+            .master_position
+            .as_deref()
+            .and_then(MasterPosition::from_name)
+        {
+            self.master_position = position;
+        }
+        if let Some([left, middle, right]) = preset.three_column_ratios.as_deref() {
+            self.three_column_ratios = [*left, *middle, *right];
+        }
+        if let Some(width) = preset.column_width {
+            self.column_width = width;
+        }
+
+        // Slots claim windows in tiling order rather than the caller's, so
+        // the same windows match the same slots every time
+        for window in windows {
+            self.track_window(window);
+        }
+        let windows = &self.in_window_order(windows)[..];
+
+        // Claimed windows go to the front of their workspace's order
+        let mut unclaimed = windows.to_vec();
+        let claimed: Vec<WindowId> = preset
+            .slots
+            .iter()
+            .filter_map(|matcher| Self::claim_window(&mut unclaimed, matcher))
+            .collect();
+        if let Some(window) = windows.first() {
+            let order = self.window_orders.entry(window.workspace_id).or_default();
+            order.retain(|id| !claimed.contains(id));
+            order.splice(0..0, claimed);
+        }
+
+        if let Some(ref node) = preset.bsp {
+            let mut unclaimed = windows.to_vec();
+            let area = self.tiling_area;
+            self.bsp_root = Self::build_bsp_node(node, &mut unclaimed, area);
+            if let Some(ref mut root) = self.bsp_root {
+                root.update_rects(area);
+            }
+            self.preselection = None;
+        }
+
+        Ok(())
     }
 
-    pub fn set_layout(&mut self, layout: LayoutType) {
-        self.current_layout = layout;
+    fn claim_window(unclaimed: &mut Vec<&Window>, matcher: &WindowMatcher) -> Option<WindowId> {
+        let index = unclaimed.iter().position(|w| matcher.matches(w))?;
+        Some(unclaimed.remove(index).id)
     }
 
-    pub fn current_layout(&self) -> &LayoutType {
-        &self.current_layout
+    fn build_bsp_node(
+        node: &PresetNode,
+        unclaimed: &mut Vec<&Window>,
+        rect: Rect,
+    ) -> Option<BSPNode> {
+        match node {
+            PresetNode::Window(matcher) => Self::claim_window(unclaimed, matcher)
+                .map(|window_id| BSPNode::new_leaf(window_id, rect)),
+            PresetNode::Split {
+                horizontal,
+                ratio,
+                first,
+                second,
+            } => {
+                let first = Self::build_bsp_node(first, unclaimed, rect);
+                let second = Self::build_bsp_node(second, unclaimed, rect);
+                match (first, second) {
+                    (Some(first), Some(second)) => {
+                        let mut container = BSPNode::new_container(rect, *horizontal, *ratio);
+                        container.left = Some(Box::new(first));
+                        container.right = Some(Box::new(second));
+                        Some(container)
+                    }
+                    (first, second) => first.or(second),
+                }
+            }
+        }
     }
 }
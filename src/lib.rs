@@ -1,10 +1,13 @@
+pub mod backend;
 pub mod config;
 pub mod focus;
 pub mod hotkeys;
 pub mod ipc;
 pub mod layout;
+#[cfg(target_os = "macos")]
 pub mod macos;
 pub mod plugins;
+pub mod preset;
 pub mod window_manager;
 
 pub use config::Config;
@@ -15,7 +18,7 @@ pub type Result<T> = anyhow::Result<T>;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct WindowId(pub u32);
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Rect {
     pub x: f64,
     pub y: f64,
@@ -32,4 +35,60 @@ impl Rect {
             height,
         }
     }
+
+    pub fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+}
+
+/// Size constraints a window reports for itself. Unset bounds leave that
+/// dimension unconstrained.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SizeHints {
+    pub min_width: Option<f64>,
+    pub min_height: Option<f64>,
+    pub max_width: Option<f64>,
+    pub max_height: Option<f64>,
+}
+
+impl SizeHints {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn min_size(&self) -> (f64, f64) {
+        (
+            self.min_width.unwrap_or(0.0),
+            self.min_height.unwrap_or(0.0),
+        )
+    }
+
+    pub fn max_size(&self) -> (f64, f64) {
+        (
+            self.max_width.unwrap_or(f64::INFINITY),
+            self.max_height.unwrap_or(f64::INFINITY),
+        )
+    }
+
+    /// Whether `rect` is at least the minimum size.
+    pub fn fits(&self, rect: &Rect) -> bool {
+        let (min_width, min_height) = self.min_size();
+        rect.width >= min_width && rect.height >= min_height
+    }
+
+    /// `rect` resized to lie within the bounds, keeping its origin.
+    pub fn constrain(&self, rect: Rect) -> Rect {
+        let (min_width, min_height) = self.min_size();
+        let (max_width, max_height) = self.max_size();
+        Rect::new(
+            rect.x,
+            rect.y,
+            rect.width.min(max_width).max(min_width),
+            rect.height.min(max_height).max(min_height),
+        )
+    }
 }
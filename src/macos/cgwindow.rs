@@ -1,4 +1,4 @@
-use crate::{Rect, Result, Window, WindowId};
+use crate::{Rect, Result, SizeHints, Window, WindowId};
 use log::{debug, warn};
 use std::collections::HashMap;
 use std::ffi::{CStr, CString};
@@ -156,6 +156,7 @@ impl CGWindowInfo {
             is_minimized: false, // We'll need to check this separately
             is_focused: false,   // We'll need to check this separately
             workspace_id: 1,     // Default workspace for now
+            size_hints: SizeHints::default(),
         })
     }
     
@@ -1,5 +1,6 @@
 use super::accessibility::AccessibilityManager;
 use super::cgwindow::CGWindowInfo;
+use crate::backend::{Display, WindowBackend};
 use crate::window_manager::WindowEvent;
 use crate::{Rect, Result, Window, WindowId};
 use core_graphics::display::{CGDisplayBounds, CGGetActiveDisplayList, CGMainDisplayID};
@@ -8,14 +9,6 @@ use std::collections::HashMap;
 use tokio::sync::mpsc;
 use tokio::time::{interval, Duration};
 
-#[derive(Debug, Clone)]
-pub struct Display {
-    pub id: u32,
-    pub rect: Rect,
-    pub is_main: bool,
-    pub name: String,
-}
-
 pub struct MacOSWindowSystem {
     accessibility: AccessibilityManager,
     event_sender: mpsc::Sender<WindowEvent>,
@@ -111,32 +104,6 @@ impl MacOSWindowSystem {
         }
     }
 
-    pub async fn start_monitoring(&self) -> Result<()> {
-        debug!("Starting window monitoring");
-
-        let sender = self.event_sender.clone();
-        tokio::spawn(async move {
-            let mut interval = interval(Duration::from_millis(500));
-            let mut last_windows = Vec::new();
-
-            loop {
-                interval.tick().await;
-
-                match CGWindowInfo::get_all_windows() {
-                    Ok(current_windows) => {
-                        Self::detect_window_changes(&sender, &last_windows, &current_windows).await;
-                        last_windows = current_windows;
-                    }
-                    Err(e) => {
-                        error!("Failed to get window list: {}", e);
-                    }
-                }
-            }
-        });
-
-        Ok(())
-    }
-
     async fn detect_window_changes(
         sender: &mpsc::Sender<WindowEvent>,
         old_windows: &[Window],
@@ -171,98 +138,44 @@ impl MacOSWindowSystem {
             }
         }
     }
+}
 
-    pub async fn get_windows(&self) -> Result<Vec<Window>> {
-        CGWindowInfo::get_all_windows()
-    }
-
-    pub async fn get_screen_rect(&self) -> Result<Rect> {
-        // Return main display rect for backward compatibility
-        self.get_main_display_rect()
-    }
-
-    pub fn get_main_display_rect(&self) -> Result<Rect> {
-        let main_display = self
-            .displays
-            .values()
-            .find(|d| d.is_main)
-            .ok_or_else(|| anyhow::anyhow!("No main display found"))?;
-        Ok(main_display.rect.clone())
-    }
-
-    pub fn get_displays(&self) -> &HashMap<u32, Display> {
-        &self.displays
-    }
-
-    pub fn get_display_for_window(&self, window: &Window) -> Option<&Display> {
-        // Find which display contains the center of the window
-        let window_center_x = window.rect.x + window.rect.width / 2.0;
-        let window_center_y = window.rect.y + window.rect.height / 2.0;
-
-        self.displays.values().find(|display| {
-            window_center_x >= display.rect.x
-                && window_center_x < display.rect.x + display.rect.width
-                && window_center_y >= display.rect.y
-                && window_center_y < display.rect.y + display.rect.height
-        })
-    }
-
-    pub fn get_display_by_id(&self, display_id: u32) -> Option<&Display> {
-        self.displays.get(&display_id)
-    }
+impl WindowBackend for MacOSWindowSystem {
+    fn start_monitoring(&mut self) -> Result<()> {
+        debug!("Starting window monitoring");
 
-    pub fn get_windows_by_display<'a>(
-        &self,
-        windows: &'a [Window],
-    ) -> HashMap<u32, Vec<&'a Window>> {
-        let mut windows_by_display: HashMap<u32, Vec<&'a Window>> = HashMap::new();
+        let sender = self.event_sender.clone();
+        tokio::spawn(async move {
+            let mut interval = interval(Duration::from_millis(500));
+            let mut last_windows = Vec::new();
 
-        // Initialize empty vectors for each display
-        for display_id in self.displays.keys() {
-            windows_by_display.insert(*display_id, Vec::new());
-        }
+            loop {
+                interval.tick().await;
 
-        // Assign windows to displays
-        for window in windows {
-            if let Some(display) = self.get_display_for_window(window) {
-                windows_by_display
-                    .get_mut(&display.id)
-                    .unwrap()
-                    .push(window);
-            } else {
-                // If window doesn't clearly belong to any display, assign to main display
-                if let Some(main_display) = self.displays.values().find(|d| d.is_main) {
-                    windows_by_display
-                        .get_mut(&main_display.id)
-                        .unwrap()
-                        .push(window);
+                match CGWindowInfo::get_all_windows() {
+                    Ok(current_windows) => {
+                        Self::detect_window_changes(&sender, &last_windows, &current_windows).await;
+                        last_windows = current_windows;
+                    }
+                    Err(e) => {
+                        error!("Failed to get window list: {}", e);
+                    }
                 }
             }
-        }
+        });
 
-        windows_by_display
+        Ok(())
     }
 
-    pub async fn move_window_to_display(
-        &mut self,
-        window_id: WindowId,
-        target_display_id: u32,
-    ) -> Result<()> {
-        if let Some(target_display) = self.displays.get(&target_display_id) {
-            // Calculate new position centered on the target display
-            let new_x = target_display.rect.x + target_display.rect.width * 0.1;
-            let new_y = target_display.rect.y + target_display.rect.height * 0.1;
-            let new_width = target_display.rect.width * 0.8;
-            let new_height = target_display.rect.height * 0.8;
+    fn get_windows(&self) -> Result<Vec<Window>> {
+        CGWindowInfo::get_all_windows()
+    }
 
-            let new_rect = Rect::new(new_x, new_y, new_width, new_height);
-            self.move_window(window_id, new_rect).await
-        } else {
-            Err(anyhow::anyhow!("Display {} not found", target_display_id))
-        }
+    fn get_displays(&self) -> &HashMap<u32, Display> {
+        &self.displays
     }
 
-    pub fn refresh_displays(&mut self) -> Result<()> {
+    fn refresh_displays(&mut self) -> Result<()> {
         self.displays = Self::get_all_displays()?;
         info!(
             "Display configuration refreshed - {} display(s) detected",
@@ -271,19 +184,19 @@ impl MacOSWindowSystem {
         Ok(())
     }
 
-    pub async fn focus_window(&mut self, window_id: WindowId) -> Result<()> {
+    fn focus_window(&mut self, window_id: WindowId) -> Result<()> {
         self.accessibility.focus_window(window_id)
     }
 
-    pub async fn move_window(&mut self, window_id: WindowId, rect: Rect) -> Result<()> {
+    fn move_window(&mut self, window_id: WindowId, rect: Rect) -> Result<()> {
         self.accessibility.move_window(window_id, rect)
     }
 
-    pub async fn close_window(&mut self, window_id: WindowId) -> Result<()> {
+    fn close_window(&mut self, window_id: WindowId) -> Result<()> {
         self.accessibility.close_window(window_id)
     }
 
-    pub async fn get_focused_window(&self) -> Result<Option<WindowId>> {
+    fn get_focused_window(&self) -> Result<Option<WindowId>> {
         self.accessibility.get_focused_window()
     }
 }
@@ -1,4 +1,5 @@
 use crate::config::PluginConfig;
+use crate::layout::Layout;
 use crate::{Result, Window, WindowId};
 use libloading::{Library, Symbol};
 use log::{debug, error, info, warn};
@@ -6,7 +7,13 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 #[cfg(feature = "scripting")]
-use mlua::Lua;
+use crate::{layout::LayoutManager, Rect};
+#[cfg(feature = "scripting")]
+use mlua::{HookTriggers, Lua};
+#[cfg(feature = "scripting")]
+use std::cell::RefCell;
+#[cfg(feature = "scripting")]
+use std::rc::Rc;
 
 pub trait Plugin {
     fn name(&self) -> &str;
@@ -15,43 +22,171 @@ pub trait Plugin {
     fn on_window_destroyed(&mut self, window: &Window) -> Result<()>;
     fn on_window_focused(&mut self, window_id: WindowId) -> Result<()>;
     fn shutdown(&mut self) -> Result<()>;
+
+    /// Layouts this plugin adds to the layout cycle. Called once at startup.
+    fn layouts(&mut self) -> Vec<Box<dyn Layout>> {
+        Vec::new()
+    }
 }
 
 pub struct PluginManager {
     config: PluginConfig,
     native_plugins: HashMap<String, Box<dyn Plugin>>,
     native_libraries: HashMap<String, Library>,
+    // Libraries whose layouts may still be registered stay mapped
+    layout_libraries: Vec<Library>,
+    layout_providers: Vec<String>,
 
     #[cfg(feature = "scripting")]
     lua_plugins: HashMap<String, LuaPlugin>,
 
     #[cfg(feature = "scripting")]
-    lua: Lua,
+    lua: Rc<Lua>,
+
+    // Layout functions passed to `register_layout`, waiting to be handed to
+    // the layout manager
+    #[cfg(feature = "scripting")]
+    lua_layouts: Rc<RefCell<Vec<(String, mlua::RegistryKey)>>>,
 }
 
+/// Instructions a Lua layout may run per call before it is stopped, so a
+/// script stuck in a loop falls back to BSP instead of hanging the manager.
+#[cfg(feature = "scripting")]
+const LUA_INSTRUCTION_LIMIT: u32 = 10_000_000;
+
 #[cfg(feature = "scripting")]
 pub struct LuaPlugin {
     name: String,
     script_path: PathBuf,
 }
 
+/// A layout implemented by a Lua function. The function is called as
+/// `layout(screen, windows, gaps)`: `screen` is the area to tile as
+/// `{x, y, width, height}`, `windows` the windows in layout order as
+/// `{id, title, owner}`, and `gaps` holds the `inner` gap. It returns one
+/// `{x, y, width, height}` table per window, in the same order.
+#[cfg(feature = "scripting")]
+pub struct LuaLayout {
+    name: String,
+    lua: Rc<Lua>,
+    function: mlua::RegistryKey,
+}
+
+#[cfg(feature = "scripting")]
+impl LuaLayout {
+    fn call(
+        &self,
+        windows: &[&Window],
+        area: Rect,
+        gap: f64,
+    ) -> mlua::Result<HashMap<WindowId, Rect>> {
+        let lua = &*self.lua;
+        let function: mlua::Function = lua.registry_value(&self.function)?;
+
+        let screen = lua.create_table()?;
+        screen.set("x", area.x)?;
+        screen.set("y", area.y)?;
+        screen.set("width", area.width)?;
+        screen.set("height", area.height)?;
+
+        let window_list = lua.create_table()?;
+        for (i, window) in windows.iter().enumerate() {
+            let entry = lua.create_table()?;
+            entry.set("id", window.id.0)?;
+            entry.set("title", window.title.as_str())?;
+            entry.set("owner", window.owner.as_str())?;
+            window_list.set(i + 1, entry)?;
+        }
+
+        let gaps = lua.create_table()?;
+        gaps.set("inner", gap)?;
+
+        lua.set_hook(
+            HookTriggers::new().every_nth_instruction(LUA_INSTRUCTION_LIMIT),
+            |_, _| {
+                Err(mlua::Error::runtime(format!(
+                    "gave up after {} instructions",
+                    LUA_INSTRUCTION_LIMIT
+                )))
+            },
+        );
+        let result = function.call::<_, mlua::Table>((screen, window_list, gaps));
+        lua.remove_hook();
+
+        let result = result?;
+        let mut rects = HashMap::new();
+        for (i, window) in windows.iter().enumerate() {
+            let rect: mlua::Table = result
+                .get(i + 1)
+                .map_err(|_| mlua::Error::runtime(format!("no rect for window {}", i + 1)))?;
+            let rect = Rect::new(
+                rect.get("x")?,
+                rect.get("y")?,
+                rect.get("width")?,
+                rect.get("height")?,
+            );
+            let finite = [rect.x, rect.y, rect.width, rect.height]
+                .iter()
+                .all(|value| value.is_finite());
+            if !finite || rect.width <= 0.0 || rect.height <= 0.0 {
+                return Err(mlua::Error::runtime(format!(
+                    "invalid rect for window {}: {:?}",
+                    i + 1,
+                    rect
+                )));
+            }
+            rects.insert(window.id, rect);
+        }
+
+        Ok(rects)
+    }
+}
+
+#[cfg(feature = "scripting")]
+impl Layout for LuaLayout {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn compute(
+        &mut self,
+        _manager: &mut LayoutManager,
+        windows: &[&Window],
+        area: Rect,
+        gap: f64,
+    ) -> Result<HashMap<WindowId, Rect>> {
+        self.call(windows, area, gap)
+            .map_err(|e| anyhow::anyhow!("Lua layout {}: {}", self.name, e))
+    }
+}
+
 impl PluginManager {
     pub fn new(config: &PluginConfig) -> Result<Self> {
         #[cfg(feature = "scripting")]
-        let lua = Lua::new();
+        let lua = Rc::new(Lua::new());
+        #[cfg(feature = "scripting")]
+        let lua_layouts = Rc::new(RefCell::new(Vec::new()));
 
         let mut manager = Self {
             config: config.clone(),
             native_plugins: HashMap::new(),
             native_libraries: HashMap::new(),
+            layout_libraries: Vec::new(),
+            layout_providers: Vec::new(),
 
             #[cfg(feature = "scripting")]
             lua_plugins: HashMap::new(),
 
             #[cfg(feature = "scripting")]
             lua,
+
+            #[cfg(feature = "scripting")]
+            lua_layouts,
         };
 
+        #[cfg(feature = "scripting")]
+        manager.register_lua_api()?;
+
         manager.load_plugins()?;
         Ok(manager)
     }
@@ -126,6 +261,21 @@ impl PluginManager {
         Ok(())
     }
 
+    /// Functions scripts can call while they load.
+    #[cfg(feature = "scripting")]
+    fn register_lua_api(&mut self) -> Result<()> {
+        let pending = Rc::clone(&self.lua_layouts);
+        let register_layout =
+            self.lua
+                .create_function(move |lua, (name, function): (String, mlua::Function)| {
+                    let key = lua.create_registry_value(function)?;
+                    pending.borrow_mut().push((name, key));
+                    Ok(())
+                })?;
+        self.lua.globals().set("register_layout", register_layout)?;
+        Ok(())
+    }
+
     #[cfg(feature = "scripting")]
     fn load_lua_plugin(&mut self, name: &str, path: &Path) -> Result<()> {
         debug!("Loading Lua plugin: {} from {:?}", name, path);
@@ -149,6 +299,32 @@ impl PluginManager {
         Ok(())
     }
 
+    /// Collect the layouts provided by the loaded native plugins and those
+    /// registered by Lua scripts.
+    pub fn layouts(&mut self) -> Vec<Box<dyn Layout>> {
+        let mut layouts = Vec::new();
+        for (name, plugin) in self.native_plugins.iter_mut() {
+            let provided = plugin.layouts();
+            if !provided.is_empty() {
+                debug!("Plugin {} provides {} layouts", name, provided.len());
+                self.layout_providers.push(name.clone());
+            }
+            layouts.extend(provided);
+        }
+
+        #[cfg(feature = "scripting")]
+        for (name, function) in self.lua_layouts.borrow_mut().drain(..) {
+            debug!("Lua script provides layout {}", name);
+            layouts.push(Box::new(LuaLayout {
+                name,
+                lua: Rc::clone(&self.lua),
+                function,
+            }));
+        }
+
+        layouts
+    }
+
     pub fn on_window_created(&mut self, window: &Window) -> Result<()> {
         debug!("Notifying plugins of window creation: {}", window.title);
 
@@ -225,7 +401,15 @@ impl PluginManager {
             let _ = plugin.shutdown();
         }
 
-        self.native_libraries.remove(name);
+        if let Some(lib) = self.native_libraries.remove(name) {
+            if self
+                .layout_providers
+                .iter()
+                .any(|provider| provider == name)
+            {
+                self.layout_libraries.push(lib);
+            }
+        }
 
         #[cfg(feature = "scripting")]
         self.lua_plugins.remove(name);
@@ -247,7 +431,11 @@ impl PluginManager {
         }
 
         self.native_plugins.clear();
-        self.native_libraries.clear();
+        for (name, lib) in self.native_libraries.drain() {
+            if self.layout_providers.contains(&name) {
+                self.layout_libraries.push(lib);
+            }
+        }
 
         #[cfg(feature = "scripting")]
         self.lua_plugins.clear();
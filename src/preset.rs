@@ -0,0 +1,240 @@
+use crate::layout::MasterPosition;
+use crate::{Result, Window};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Identifies a window by its application and, optionally, its title. `*`
+/// in a pattern matches any run of characters, and case is ignored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowMatcher {
+    pub owner: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
+impl WindowMatcher {
+    /// A matcher for any window of the same application. Titles change too
+    /// often to be worth saving.
+    pub fn for_window(window: &Window) -> Self {
+        Self {
+            owner: window.owner.clone(),
+            title: None,
+        }
+    }
+
+    pub fn matches(&self, window: &Window) -> bool {
+        glob_match(&self.owner, &window.owner)
+            && self
+                .title
+                .as_ref()
+                .is_none_or(|title| glob_match(title, &window.title))
+    }
+}
+
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    // Greedy match, backtracking to the last `*` on a mismatch
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// A saved BSP tree, with windows identified by matcher.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresetNode {
+    Window(WindowMatcher),
+    Split {
+        horizontal: bool,
+        ratio: f64,
+        first: Box<PresetNode>,
+        second: Box<PresetNode>,
+    },
+}
+
+/// A named arrangement: the layout, its settings and which window goes
+/// where. Settings left out keep their current value when the preset is
+/// applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LayoutPreset {
+    pub layout: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub split_ratio: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nmaster: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub master_position: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub three_column_ratios: Option<Vec<f64>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub column_width: Option<f64>,
+    /// Windows in layout order, so the first fills the master area of the
+    /// Stack layout, the first slot of a template and so on.
+    #[serde(default)]
+    pub slots: Vec<WindowMatcher>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bsp: Option<PresetNode>,
+}
+
+impl LayoutPreset {
+    pub fn validate(&self) -> Result<()> {
+        if let Some(ratio) = self.split_ratio {
+            if !(ratio > 0.0 && ratio < 1.0) {
+                return Err(anyhow::anyhow!(
+                    "split_ratio must be between 0 and 1, got {}",
+                    ratio
+                ));
+            }
+        }
+
+        if let Some(ref position) = self.master_position {
+            if MasterPosition::from_name(position).is_none() {
+                return Err(anyhow::anyhow!(
+                    "master_position must be one of left, right, top or bottom, got '{}'",
+                    position
+                ));
+            }
+        }
+
+        if let Some(ref ratios) = self.three_column_ratios {
+            let positive = ratios.iter().all(|ratio| ratio.is_finite() && *ratio > 0.0);
+            if ratios.len() != 3 || !positive {
+                return Err(anyhow::anyhow!(
+                    "three_column_ratios must be three positive numbers, got {:?}",
+                    ratios
+                ));
+            }
+        }
+
+        if let Some(width) = self.column_width {
+            if !(0.1..=1.0).contains(&width) {
+                return Err(anyhow::anyhow!(
+                    "column_width must be between 0.1 and 1, got {}",
+                    width
+                ));
+            }
+        }
+
+        let mut nodes: Vec<&PresetNode> = self.bsp.iter().collect();
+        while let Some(node) = nodes.pop() {
+            if let PresetNode::Split {
+                ratio,
+                first,
+                second,
+                ..
+            } = node
+            {
+                if !(*ratio > 0.0 && *ratio < 1.0) {
+                    return Err(anyhow::anyhow!(
+                        "BSP split ratios must be between 0 and 1, got {}",
+                        ratio
+                    ));
+                }
+                nodes.push(first);
+                nodes.push(second);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Presets kept as one TOML file each in a directory.
+pub struct PresetStore {
+    dir: PathBuf,
+}
+
+impl PresetStore {
+    /// A store for the presets in `dir`, where a leading `~` stands for the
+    /// home directory.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        let dir = match (dir.strip_prefix("~"), std::env::var_os("HOME")) {
+            (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+            _ => dir,
+        };
+        Self { dir }
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(anyhow::anyhow!(
+                "Preset names may only contain letters, digits, '-' and '_', got '{}'",
+                name
+            ));
+        }
+        Ok(self.dir.join(format!("{}.toml", name)))
+    }
+
+    pub fn save(&self, name: &str, preset: &LayoutPreset) -> Result<()> {
+        let path = self.path(name)?;
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(path, toml::to_string_pretty(preset)?)?;
+        Ok(())
+    }
+
+    pub fn load(&self, name: &str) -> Result<LayoutPreset> {
+        let path = self.path(name)?;
+        if !path.exists() {
+            return Err(anyhow::anyhow!("No preset named '{}'", name));
+        }
+
+        let preset: LayoutPreset = toml::from_str(&std::fs::read_to_string(&path)?)?;
+        preset
+            .validate()
+            .map_err(|e| anyhow::anyhow!("Preset '{}' is invalid: {}", name, e))?;
+        Ok(preset)
+    }
+
+    /// Names of the saved presets, sorted.
+    pub fn list(&self) -> Result<Vec<String>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "toml")
+            {
+                if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn delete(&self, name: &str) -> Result<()> {
+        let path = self.path(name)?;
+        if !path.exists() {
+            return Err(anyhow::anyhow!("No preset named '{}'", name));
+        }
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+}
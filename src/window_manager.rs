@@ -1,14 +1,21 @@
+use crate::backend::WindowBackend;
+use crate::config::MAX_GAP;
 use crate::focus::FocusManager;
 use crate::hotkeys::HotkeyManager;
 use crate::ipc::IpcServer;
 use crate::layout::LayoutManager;
-use crate::macos::MacOSWindowSystem;
 use crate::plugins::PluginManager;
-use crate::{Config, Rect, Result, WindowId};
-use log::{debug, error, info};
+use crate::preset::PresetStore;
+use crate::{Config, Rect, Result, SizeHints, WindowId};
+use log::{debug, error, info, warn};
 use std::collections::HashMap;
-use tokio::sync::mpsc;
-use tokio::time::{interval, Duration};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{interval, Duration, Instant};
+
+/// How long a dragged window has to stay put before it counts as dropped.
+/// This is longer than the macOS backend's 500ms poll, so a drag ends once
+/// a whole poll passes without the window moving.
+const DRAG_SETTLE_TIME: Duration = Duration::from_millis(600);
 
 #[derive(Debug, Clone)]
 pub struct Window {
@@ -19,6 +26,7 @@ pub struct Window {
     pub is_minimized: bool,
     pub is_focused: bool,
     pub workspace_id: u32,
+    pub size_hints: SizeHints,
 }
 
 #[derive(Debug)]
@@ -32,8 +40,14 @@ pub enum WindowEvent {
     WindowUnminimized(WindowId),
     WorkspaceChanged(u32),
     MouseMoved { x: f64, y: f64 },
+    MouseReleased { x: f64, y: f64 },
 }
 
+/// Channel a query command answers on, used by IPC commands that report
+/// data back to the client. An `{"error": ...}` answer fails the request
+/// with that message.
+pub type Reply = oneshot::Sender<serde_json::Value>;
+
 #[derive(Debug)]
 pub enum Command {
     FocusWindow(WindowId),
@@ -43,21 +57,97 @@ pub enum Command {
     CloseFocusedWindow,
     MoveWindow(WindowId, Rect),
     ToggleLayout,
+    PreviousLayout,
+    SetLayout(String, Option<Reply>),
     ToggleFloat,
     ToggleFullscreen,
     SwapMain,
+    MoveToFirst,
+    MoveToLast,
+    SwapWindows(WindowId, WindowId),
+    Preselect(crate::hotkeys::Direction, Option<f64>),
+    CancelPreselect,
+    ToggleSplit,
+    Rotate(crate::layout::Rotation),
+    Mirror(crate::layout::MirrorAxis),
+    Balance,
+    Resize(crate::hotkeys::Direction, f64),
+    IncMaster,
+    DecMaster,
+    SetMasterPosition(crate::layout::MasterPosition),
+    AdjustWeight(f64),
+    ResetWeight,
+    AdjustColumnWidth(f64),
+    MoveColumn(crate::hotkeys::Direction),
+    SetContainerStyle(Option<crate::layout::ContainerStyle>),
+    CycleContainer(bool),
+    CycleWindow(bool),
+    AdjustGap(f64),
+    SetGap(f64),
+    LayoutCommand(String, Vec<String>),
+    EnableLayoutRules,
+    LayoutUndo,
+    LayoutRedo,
+    SavePreset(String, Option<Reply>),
+    ApplyPreset(String, Option<Reply>),
+    DeletePreset(String, Option<Reply>),
+    ListPresets(Option<Reply>),
     ReloadConfig,
     ListWindows,
-    GetStatus,
+    GetStatus(Option<Reply>),
     Quit,
 }
 
+impl Command {
+    /// Whether the command can change the arrangement, so the state before
+    /// it goes into the undo history.
+    fn changes_layout(&self) -> bool {
+        matches!(
+            self,
+            Command::MoveDirection(_)
+                | Command::ToggleLayout
+                | Command::PreviousLayout
+                | Command::SetLayout(_, _)
+                | Command::SwapMain
+                | Command::MoveToFirst
+                | Command::MoveToLast
+                | Command::SwapWindows(_, _)
+                | Command::ToggleSplit
+                | Command::Rotate(_)
+                | Command::Mirror(_)
+                | Command::Balance
+                | Command::Resize(_, _)
+                | Command::IncMaster
+                | Command::DecMaster
+                | Command::SetMasterPosition(_)
+                | Command::AdjustWeight(_)
+                | Command::ResetWeight
+                | Command::AdjustColumnWidth(_)
+                | Command::MoveColumn(_)
+                | Command::SetContainerStyle(_)
+                | Command::LayoutCommand(_, _)
+                | Command::ApplyPreset(_, _)
+        )
+    }
+}
+
+/// A tiled window being dragged with the mouse.
+struct Drag {
+    window_id: WindowId,
+    last_moved: Instant,
+}
+
 pub struct WindowManager {
     config: Config,
     windows: HashMap<WindowId, Window>,
     current_workspace: u32,
+    // Rect each window was last moved to by a layout, to tell apps that
+    // refuse a size from manual resizes
+    requested_rects: HashMap<WindowId, Rect>,
+    drag: Option<Drag>,
+    cursor: Option<(f64, f64)>,
 
-    macos: MacOSWindowSystem,
+    backend: Box<dyn WindowBackend>,
     layout_manager: LayoutManager,
     focus_manager: FocusManager,
     ipc_server: IpcServer,
@@ -72,20 +162,52 @@ pub struct WindowManager {
 impl WindowManager {
     pub async fn new(config: Config) -> Result<Self> {
         let (event_tx, event_rx) = mpsc::channel(1000);
+
+        #[cfg(target_os = "macos")]
+        let backend: Box<dyn WindowBackend> =
+            Box::new(crate::macos::MacOSWindowSystem::new(event_tx.clone()).await?);
+        #[cfg(not(target_os = "macos"))]
+        let backend: Box<dyn WindowBackend> =
+            Box::new(crate::backend::HeadlessBackend::new(event_tx.clone()));
+
+        Self::with_backend(config, backend, event_tx, event_rx).await
+    }
+
+    /// Build a window manager on top of an explicit backend. `event_tx` must be
+    /// the sender the backend was created with so its events reach `event_rx`.
+    pub async fn with_backend(
+        config: Config,
+        backend: Box<dyn WindowBackend>,
+        event_tx: mpsc::Sender<WindowEvent>,
+        event_rx: mpsc::Receiver<WindowEvent>,
+    ) -> Result<Self> {
         let (command_tx, command_rx) = mpsc::channel(1000);
 
-        let macos = MacOSWindowSystem::new(event_tx.clone()).await?;
-        let layout_manager = LayoutManager::new(&config.layout);
+        let mut layout_manager = LayoutManager::new(&config.layout);
         let focus_manager = FocusManager::new(&config.focus, event_tx.clone());
         let ipc_server = IpcServer::new(&config.ipc, command_tx.clone()).await?;
         let hotkey_manager = HotkeyManager::new(&config.hotkeys, command_tx.clone())?;
-        let plugin_manager = PluginManager::new(&config.plugins)?;
+        let mut plugin_manager = PluginManager::new(&config.plugins)?;
+
+        for layout in plugin_manager.layouts() {
+            let name = layout.name().to_string();
+            match layout_manager.register(layout) {
+                Ok(()) => info!("Registered plugin layout: {}", name),
+                Err(e) => error!("Failed to register plugin layout {}: {}", name, e),
+            }
+        }
+        layout_manager.validate_config(&config.layout)?;
+        layout_manager.validate_bindings(&config.hotkeys)?;
+        layout_manager.set_default_layout(&config.layout.default_layout);
 
         Ok(Self {
             config,
             windows: HashMap::new(),
             current_workspace: 1,
-            macos,
+            requested_rects: HashMap::new(),
+            drag: None,
+            cursor: None,
+            backend,
             layout_manager,
             focus_manager,
             ipc_server,
@@ -100,7 +222,7 @@ impl WindowManager {
     pub async fn run(&mut self) -> Result<()> {
         info!("Starting window manager event loop");
 
-        self.macos.start_monitoring().await?;
+        self.backend.start_monitoring()?;
         self.focus_manager.start().await?;
         self.ipc_server.start().await?;
         self.hotkey_manager.start().await?;
@@ -123,40 +245,63 @@ impl WindowManager {
                     if let Err(e) = self.refresh_windows().await {
                         error!("Error refreshing windows: {}", e);
                     }
+                    if let Err(e) = self.settle_drag().await {
+                        error!("Error dropping dragged window: {}", e);
+                    }
                 }
             }
         }
     }
 
-    async fn handle_window_event(&mut self, event: WindowEvent) -> Result<()> {
+    /// Handle every event already queued on the event channel without waiting
+    /// for new ones.
+    pub async fn process_pending_events(&mut self) -> Result<()> {
+        while let Ok(event) = self.event_rx.try_recv() {
+            self.handle_window_event(event).await?;
+        }
+        Ok(())
+    }
+
+    pub fn windows(&self) -> &HashMap<WindowId, Window> {
+        &self.windows
+    }
+
+    pub fn layout_manager(&self) -> &LayoutManager {
+        &self.layout_manager
+    }
+
+    pub async fn handle_window_event(&mut self, event: WindowEvent) -> Result<()> {
         debug!("Handling window event: {:?}", event);
 
         match event {
             WindowEvent::WindowCreated(window) => {
+                self.layout_manager.track_window(&window);
                 self.windows.insert(window.id, window.clone());
                 self.apply_layout().await?;
                 self.plugin_manager.on_window_created(&window)?;
             }
             WindowEvent::WindowDestroyed(id) => {
                 if let Some(window) = self.windows.remove(&id) {
+                    self.requested_rects.remove(&id);
+                    self.layout_manager.forget_window(id);
                     self.apply_layout().await?;
                     self.plugin_manager.on_window_destroyed(&window)?;
                 }
             }
             WindowEvent::WindowMoved(id, new_rect) => {
-                if let Some(window) = self.windows.get_mut(&id) {
-                    window.rect = new_rect;
-                }
+                self.handle_window_rect_change(id, new_rect).await?;
             }
             WindowEvent::WindowResized(id, new_rect) => {
-                if let Some(window) = self.windows.get_mut(&id) {
-                    window.rect = new_rect;
-                }
+                self.handle_window_rect_change(id, new_rect).await?;
             }
             WindowEvent::WindowFocused(id) => {
                 for window in self.windows.values_mut() {
                     window.is_focused = window.id == id;
                 }
+                self.layout_manager.window_focused(id);
+                if self.layout_manager.follows_focus() {
+                    self.apply_layout().await?;
+                }
                 self.plugin_manager.on_window_focused(id)?;
             }
             WindowEvent::WindowMinimized(id) => {
@@ -173,40 +318,187 @@ impl WindowManager {
             }
             WindowEvent::WorkspaceChanged(workspace) => {
                 self.current_workspace = workspace;
+                self.layout_manager.switch_workspace(workspace);
                 self.refresh_windows().await?;
+                self.apply_layout().await?;
             }
             WindowEvent::MouseMoved { x, y } => {
+                self.cursor = Some((x, y));
                 self.focus_manager
                     .handle_mouse_move(x, y, &self.windows)
                     .await?;
             }
+            WindowEvent::MouseReleased { x, y } => {
+                self.cursor = Some((x, y));
+                self.finish_drag().await?;
+            }
         }
 
         Ok(())
     }
 
-    async fn handle_command(&mut self, command: Command) -> Result<()> {
+    async fn handle_window_rect_change(&mut self, id: WindowId, new_rect: Rect) -> Result<()> {
+        let old_rect = match self.windows.get_mut(&id) {
+            Some(window) => std::mem::replace(&mut window.rect, new_rect),
+            None => return Ok(()),
+        };
+
+        // Hidden group members move when they are parked; treating that as
+        // a drag or resize would re-tile and park them again
+        if !self.is_tiled(id) || self.layout_manager.is_hidden(id) {
+            return Ok(());
+        }
+
+        if same_size(&old_rect, &new_rect) {
+            // The backend reports positions along the way, so wait for the
+            // drag to end before working out where the window landed
+            if !same_rect(&old_rect, &new_rect) {
+                self.drag = Some(Drag {
+                    window_id: id,
+                    last_moved: Instant::now(),
+                });
+            }
+            return Ok(());
+        }
+
+        // Apps with a minimum or maximum size answer a layout's move with
+        // a size of their own. That is not a manual resize, so leave the
+        // splits alone.
+        let requested = self.requested_rects.get(&id).copied();
+        if let Some(requested) = requested.filter(|rect| same_rect(rect, &old_rect)) {
+            if self.refused_size(id, requested, new_rect) {
+                debug!("Window {:?} kept its own size over {:?}", id, requested);
+                return Ok(());
+            }
+        }
+
+        // A tiled window whose size no longer matches its tile was resized
+        // by hand; fold the new size into the layout and re-tile around it.
+        if self.layout_manager.resize_to_rect(id, old_rect, new_rect) {
+            debug!("Window {:?} resized manually to {:?}", id, new_rect);
+            self.apply_layout().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether a window moved to `requested` answered with the size its
+    /// size hints allow rather than the one it was given.
+    fn refused_size(&self, id: WindowId, requested: Rect, new_rect: Rect) -> bool {
+        self.windows
+            .get(&id)
+            .map(|window| window.size_hints.constrain(requested))
+            .is_some_and(|allowed| same_size(&allowed, &new_rect))
+    }
+
+    /// Drop the dragged window once it has not moved for a whole backend
+    /// poll. This is how drags end on backends that do not report the mouse
+    /// button being released.
+    async fn settle_drag(&mut self) -> Result<()> {
+        let settled = self
+            .drag
+            .as_ref()
+            .is_some_and(|drag| drag.last_moved.elapsed() >= DRAG_SETTLE_TIME);
+        if settled {
+            self.finish_drag().await?;
+        }
+        Ok(())
+    }
+
+    async fn finish_drag(&mut self) -> Result<()> {
+        let Some(drag) = self.drag.take() else {
+            return Ok(());
+        };
+        let Some(window) = self.windows.get(&drag.window_id) else {
+            return Ok(());
+        };
+
+        // The cursor holds the window while dragging it, so a position
+        // outside the window is stale
+        let (x, y) = self
+            .cursor
+            .filter(|&(x, y)| window.rect.contains(x, y))
+            .unwrap_or_else(|| window.rect.center());
+        self.handle_window_drop(drag.window_id, x, y).await
+    }
+
+    /// A tiled window was dropped at the cursor position `(x, y)`: dropping
+    /// it on the centre of another tile swaps the two, dropping it near an
+    /// edge re-inserts it on that side. Anything else snaps it back into its
+    /// tile.
+    async fn handle_window_drop(&mut self, id: WindowId, x: f64, y: f64) -> Result<()> {
+        let before = self.layout_manager.save_state();
+        let target = self
+            .focus_manager
+            .get_windows_under_cursor(x, y, &self.windows)
+            .into_iter()
+            .find(|target_id| *target_id != id && self.is_tiled(*target_id));
+
+        if let Some(target_id) = target {
+            let target_rect = self.windows[&target_id].rect;
+            match crate::layout::drop_zone(&target_rect, x, y) {
+                None => {
+                    if self.layout_manager.swap_windows(id, target_id) {
+                        info!("Swapped window {:?} with {:?}", id, target_id);
+                    }
+                }
+                Some(direction) => {
+                    if self
+                        .layout_manager
+                        .move_window_beside(id, target_id, direction)
+                    {
+                        info!("Moved window {:?} {:?} of {:?}", id, direction, target_id);
+                    }
+                }
+            }
+        }
+
+        self.layout_manager.record_history(before);
+        self.apply_layout().await
+    }
+
+    fn is_tiled(&self, id: WindowId) -> bool {
+        self.windows
+            .get(&id)
+            .is_some_and(|w| w.workspace_id == self.current_workspace && !w.is_minimized)
+    }
+
+    pub async fn handle_command(&mut self, command: Command) -> Result<()> {
         debug!("Handling command: {:?}", command);
 
+        let before = command
+            .changes_layout()
+            .then(|| self.layout_manager.save_state());
+
         match command {
             Command::FocusWindow(id) => {
                 if self.windows.contains_key(&id) {
-                    self.macos.focus_window(id).await?;
+                    self.backend.focus_window(id)?;
                 }
             }
             Command::CloseWindow(id) => {
                 if self.windows.contains_key(&id) {
-                    self.macos.close_window(id).await?;
+                    self.backend.close_window(id)?;
                 }
             }
             Command::MoveWindow(id, rect) => {
                 if self.windows.contains_key(&id) {
-                    self.macos.move_window(id, rect).await?;
+                    self.backend.move_window(id, rect)?;
                 }
             }
             Command::FocusDirection(direction) => {
+                // Every monocle window has the same rect, so step through the
+                // monocle list instead of searching by position
+                if self.layout_manager.get_current_layout() == crate::layout::MONOCLE_LAYOUT {
+                    let forward = matches!(
+                        direction,
+                        crate::hotkeys::Direction::Right | crate::hotkeys::Direction::Down
+                    );
+                    return self.cycle_monocle(forward).await;
+                }
+
                 if let Some(target_id) = self.find_window_in_direction(direction) {
-                    self.macos.focus_window(target_id).await?;
+                    self.backend.focus_window(target_id)?;
                     info!("Focused window in direction {:?}", direction);
                 } else {
                     debug!("No window found in direction {:?}", direction);
@@ -215,15 +507,8 @@ impl WindowManager {
             Command::MoveDirection(direction) => {
                 if let Some(focused_id) = self.get_focused_window_id() {
                     if let Some(target_id) = self.find_window_in_direction(direction) {
-                        // For now, just swap the focused window with the target
-                        if let (Some(focused_window), Some(target_window)) = 
-                            (self.windows.get(&focused_id), self.windows.get(&target_id)) {
-                            let focused_rect = focused_window.rect;
-                            let target_rect = target_window.rect;
-                            
-                            self.macos.move_window(focused_id, target_rect).await?;
-                            self.macos.move_window(target_id, focused_rect).await?;
-                            
+                        if self.layout_manager.swap_windows(focused_id, target_id) {
+                            self.apply_layout().await?;
                             info!("Swapped windows in direction {:?}", direction);
                         }
                     }
@@ -231,15 +516,33 @@ impl WindowManager {
             }
             Command::CloseFocusedWindow => {
                 if let Some(focused_id) = self.get_focused_window_id() {
-                    self.macos.close_window(focused_id).await?;
+                    self.backend.close_window(focused_id)?;
                     info!("Closed focused window");
                 }
             }
             Command::ToggleLayout => {
                 self.layout_manager.toggle_layout();
+                self.disable_layout_rules();
                 self.apply_layout().await?;
                 info!("Toggled layout to: {:?}", self.layout_manager.get_current_layout());
             }
+            Command::PreviousLayout => {
+                self.layout_manager.previous_layout();
+                self.disable_layout_rules();
+                self.apply_layout().await?;
+                info!(
+                    "Switched back to layout: {}",
+                    self.layout_manager.get_current_layout()
+                );
+            }
+            Command::SetLayout(name, reply) => {
+                let result = self.set_layout(&name).await;
+                answer(reply, result)?;
+                info!(
+                    "Switched to layout: {}",
+                    self.layout_manager.get_current_layout()
+                );
+            }
             Command::ToggleFloat => {
                 if let Some(_focused_id) = self.get_focused_window_id() {
                     // For now, just apply layout - a full implementation would track floating state
@@ -250,37 +553,229 @@ impl WindowManager {
             Command::ToggleFullscreen => {
                 if let Some(focused_id) = self.get_focused_window_id() {
                     // Get screen rect and move window to fill it
-                    let screen_rect = self.macos.get_screen_rect().await?;
-                    self.macos.move_window(focused_id, screen_rect).await?;
+                    let screen_rect = self.backend.get_screen_rect()?;
+                    self.backend.move_window(focused_id, screen_rect)?;
                     info!("Toggled fullscreen for focused window");
                 }
             }
             Command::SwapMain => {
                 if let Some(focused_id) = self.get_focused_window_id() {
-                    // Find the "main" window (first in layout order) and swap with focused
-                    let workspace_windows: Vec<&Window> = self
-                        .windows
-                        .values()
-                        .filter(|w| w.workspace_id == self.current_workspace && !w.is_minimized)
-                        .collect();
-                    
-                    if let Some(main_window) = workspace_windows.first() {
-                        let main_id = main_window.id;
-                        if main_id != focused_id {
-                            if let (Some(focused_window), Some(main_window)) = 
-                                (self.windows.get(&focused_id), self.windows.get(&main_id)) {
-                                let focused_rect = focused_window.rect;
-                                let main_rect = main_window.rect;
-                                
-                                self.macos.move_window(focused_id, main_rect).await?;
-                                self.macos.move_window(main_id, focused_rect).await?;
-                                
-                                info!("Swapped focused window with main window");
+                    if self.layout_manager.swap_main(focused_id) {
+                        self.apply_layout().await?;
+                        info!("Swapped focused window with main window");
+                    }
+                }
+            }
+            Command::MoveToFirst => {
+                if let Some(focused_id) = self.get_focused_window_id() {
+                    if self.layout_manager.move_to_first(focused_id) {
+                        self.apply_layout().await?;
+                        info!("Moved window {:?} to the front", focused_id);
+                    }
+                }
+            }
+            Command::MoveToLast => {
+                if let Some(focused_id) = self.get_focused_window_id() {
+                    if self.layout_manager.move_to_last(focused_id) {
+                        self.apply_layout().await?;
+                        info!("Moved window {:?} to the back", focused_id);
+                    }
+                }
+            }
+            Command::SwapWindows(a, b) => {
+                if self.layout_manager.swap_windows(a, b) {
+                    self.apply_layout().await?;
+                    info!("Swapped windows {:?} and {:?}", a, b);
+                }
+            }
+            Command::Preselect(direction, ratio) => {
+                if let Some(focused_id) = self.get_focused_window_id() {
+                    self.layout_manager.preselect(focused_id, direction, ratio);
+                    info!("Preselected {:?} of window {:?}", direction, focused_id);
+                } else {
+                    debug!("No focused window to preselect against");
+                }
+            }
+            Command::CancelPreselect => {
+                self.layout_manager.cancel_preselection();
+                info!("Cancelled preselection");
+            }
+            Command::ToggleSplit => {
+                if let Some(focused_id) = self.get_focused_window_id() {
+                    if self.layout_manager.toggle_split(focused_id) {
+                        self.apply_layout().await?;
+                        info!("Toggled split orientation around window {:?}", focused_id);
+                    }
+                }
+            }
+            Command::Rotate(rotation) => {
+                if self.layout_manager.rotate_tree(rotation) {
+                    self.apply_layout().await?;
+                    info!("Rotated layout tree by {:?}", rotation);
+                }
+            }
+            Command::Mirror(axis) => {
+                if self.layout_manager.mirror_tree(axis) {
+                    self.apply_layout().await?;
+                    info!("Mirrored layout tree across {:?} axis", axis);
+                }
+            }
+            Command::Balance => {
+                if self.layout_manager.balance_tree() {
+                    self.apply_layout().await?;
+                    info!("Balanced layout tree");
+                }
+            }
+            Command::Resize(direction, pixels) => {
+                let focused_id = self.get_focused_window_id();
+                if self.layout_manager.resize(focused_id, direction, pixels) {
+                    self.apply_layout().await?;
+                    info!("Resized {:?} by {}px", direction, pixels);
+                } else {
+                    debug!("Nothing to resize {:?} in current layout", direction);
+                }
+            }
+            Command::IncMaster => {
+                self.layout_manager.inc_master();
+                self.apply_layout().await?;
+                info!("Master count: {}", self.layout_manager.get_nmaster());
+            }
+            Command::DecMaster => {
+                self.layout_manager.dec_master();
+                self.apply_layout().await?;
+                info!("Master count: {}", self.layout_manager.get_nmaster());
+            }
+            Command::SetMasterPosition(position) => {
+                self.layout_manager.set_master_position(position);
+                self.apply_layout().await?;
+                info!("Master area moved to the {}", position.name());
+            }
+            Command::AdjustWeight(delta) => {
+                if let Some(focused_id) = self.get_focused_window_id() {
+                    let weight = self.layout_manager.adjust_window_weight(focused_id, delta);
+                    self.apply_layout().await?;
+                    info!("Window {:?} weight: {}", focused_id, weight);
+                }
+            }
+            Command::ResetWeight => {
+                if let Some(focused_id) = self.get_focused_window_id() {
+                    self.layout_manager.reset_window_weight(focused_id);
+                    self.apply_layout().await?;
+                    info!("Reset weight of window {:?}", focused_id);
+                }
+            }
+            Command::AdjustColumnWidth(delta) => {
+                if let Some(focused_id) = self.get_focused_window_id() {
+                    let width = self.layout_manager.adjust_column_width(focused_id, delta);
+                    self.apply_layout().await?;
+                    info!("Window {:?} column width: {}", focused_id, width);
+                }
+            }
+            Command::MoveColumn(direction) => {
+                if let Some(focused_id) = self.get_focused_window_id() {
+                    if self.layout_manager.move_column(focused_id, direction) {
+                        self.apply_layout().await?;
+                        info!("Moved column of window {:?} {:?}", focused_id, direction);
+                    }
+                }
+            }
+            Command::SetContainerStyle(style) => {
+                if let Some(focused_id) = self.get_focused_window_id() {
+                    if self.layout_manager.set_container_style(focused_id, style) {
+                        self.apply_layout().await?;
+                        match style {
+                            Some(style) => {
+                                info!(
+                                    "Window {:?} now in a {} container",
+                                    focused_id,
+                                    style.name()
+                                )
                             }
+                            None => info!("Split up the container of window {:?}", focused_id),
+                        }
+                    }
+                }
+            }
+            Command::CycleContainer(forward) => {
+                if let Some(focused_id) = self.get_focused_window_id() {
+                    if let Some(next_id) = self.layout_manager.cycle_container(focused_id, forward)
+                    {
+                        // Move focus before re-tiling so the layout keeps the
+                        // new window in front
+                        for window in self.windows.values_mut() {
+                            window.is_focused = window.id == next_id;
                         }
+                        self.apply_layout().await?;
+                        self.backend.focus_window(next_id)?;
+                        info!("Switched container to window {:?}", next_id);
                     }
                 }
             }
+            Command::CycleWindow(forward) => {
+                self.cycle_monocle(forward).await?;
+            }
+            Command::AdjustGap(delta) => {
+                let general = &self.config.general;
+                let (inner_gap, outer_gap) = (general.inner_gap + delta, general.outer_gap + delta);
+                self.set_gaps(inner_gap, outer_gap).await?;
+            }
+            Command::SetGap(gap) => {
+                self.set_gaps(gap, gap).await?;
+            }
+            Command::LayoutCommand(command, args) => {
+                match self.layout_manager.layout_command(&command, &args) {
+                    Ok(true) => self.apply_layout().await?,
+                    Ok(false) => {}
+                    Err(e) => error!("Layout command {} failed: {}", command, e),
+                }
+            }
+            Command::EnableLayoutRules => {
+                if self.layout_manager.enable_rules(self.current_workspace) {
+                    self.apply_layout().await?;
+                    info!(
+                        "Layout rules back on for workspace {}",
+                        self.current_workspace
+                    );
+                }
+            }
+            Command::LayoutUndo => {
+                if self.layout_manager.undo() {
+                    self.apply_layout().await?;
+                    info!("Undid layout change");
+                } else {
+                    debug!("No layout change to undo");
+                }
+            }
+            Command::LayoutRedo => {
+                if self.layout_manager.redo() {
+                    self.apply_layout().await?;
+                    info!("Redid layout change");
+                } else {
+                    debug!("No layout change to redo");
+                }
+            }
+            Command::SavePreset(name, reply) => {
+                let result = self.save_preset(&name);
+                answer(reply, result)?;
+                info!("Saved layout preset {}", name);
+            }
+            Command::ApplyPreset(name, reply) => {
+                let result = self.apply_preset(&name).await;
+                answer(reply, result)?;
+                info!("Applied layout preset {}", name);
+            }
+            Command::DeletePreset(name, reply) => {
+                let result = self.presets().delete(&name);
+                answer(reply, result)?;
+                info!("Deleted layout preset {}", name);
+            }
+            Command::ListPresets(reply) => {
+                let names = self.presets().list()?;
+                info!("Layout presets: {:?}", names);
+                if let Some(reply) = reply {
+                    let _ = reply.send(serde_json::json!({ "presets": names }));
+                }
+            }
             Command::ReloadConfig => {
                 info!("Reloading configuration");
             }
@@ -289,11 +784,14 @@ impl WindowManager {
                     info!("Window {}: {} ({})", id.0, window.title, window.owner);
                 }
             }
-            Command::GetStatus => {
+            Command::GetStatus(reply) => {
                 info!(
                     "Window manager status: {} windows managed",
                     self.windows.len()
                 );
+                if let Some(reply) = reply {
+                    let _ = reply.send(self.status());
+                }
             }
             Command::Quit => {
                 info!("Shutting down window manager");
@@ -301,9 +799,132 @@ impl WindowManager {
             }
         }
 
+        if let Some(before) = before {
+            self.layout_manager.record_history(before);
+        }
+
         Ok(())
     }
     
+    /// Show the next or previous monocle window and give it focus.
+    async fn cycle_monocle(&mut self, forward: bool) -> Result<()> {
+        match self.layout_manager.cycle_monocle(forward) {
+            Some(next_id) => {
+                for window in self.windows.values_mut() {
+                    window.is_focused = window.id == next_id;
+                }
+                self.apply_layout().await?;
+                self.backend.focus_window(next_id)?;
+                if let Some((position, count)) = self.layout_manager.monocle_position() {
+                    info!("Monocle window {}/{}", position, count);
+                }
+            }
+            None => debug!("Window cycling only applies to the monocle layout"),
+        }
+        Ok(())
+    }
+
+    /// Change the inner and outer gaps for this session and re-tile.
+    async fn set_gaps(&mut self, inner_gap: f64, outer_gap: f64) -> Result<()> {
+        let general = &mut self.config.general;
+        general.inner_gap = inner_gap.clamp(0.0, MAX_GAP);
+        general.outer_gap = outer_gap.clamp(0.0, MAX_GAP);
+        info!(
+            "Gaps: inner {}, outer {}",
+            general.inner_gap, general.outer_gap
+        );
+        self.apply_layout().await
+    }
+
+    async fn set_layout(&mut self, name: &str) -> Result<()> {
+        if !self.layout_manager.set_layout(name) {
+            return Err(anyhow::anyhow!(
+                "Unknown layout '{}', expected one of {:?}",
+                name,
+                self.layout_manager.layout_names()
+            ));
+        }
+        self.disable_layout_rules();
+        self.apply_layout().await
+    }
+
+    /// A layout picked by hand stays with the current workspace and turns
+    /// the layout rules off there.
+    fn disable_layout_rules(&mut self) {
+        if self.layout_manager.disable_rules(self.current_workspace) {
+            info!(
+                "Layout rules off for workspace {} until layout_auto",
+                self.current_workspace
+            );
+        }
+    }
+
+    fn presets(&self) -> PresetStore {
+        PresetStore::new(&self.config.layout.preset_dir)
+    }
+
+    fn save_preset(&self, name: &str) -> Result<()> {
+        let workspace_windows: Vec<&Window> = self
+            .windows
+            .values()
+            .filter(|w| w.workspace_id == self.current_workspace && !w.is_minimized)
+            .collect();
+        let preset = self.layout_manager.snapshot(&workspace_windows);
+        self.presets().save(name, &preset)
+    }
+
+    async fn apply_preset(&mut self, name: &str) -> Result<()> {
+        let preset = self.presets().load(name)?;
+        let workspace_windows: Vec<&Window> = self
+            .windows
+            .values()
+            .filter(|w| w.workspace_id == self.current_workspace && !w.is_minimized)
+            .collect();
+        self.layout_manager
+            .apply_preset(&preset, &workspace_windows)?;
+        self.disable_layout_rules();
+        self.apply_layout().await
+    }
+
+    fn status(&self) -> serde_json::Value {
+        let monocle = self
+            .layout_manager
+            .monocle_position()
+            .map(|(position, count)| format!("{}/{}", position, count));
+
+        let title_bars: Vec<_> = self
+            .layout_manager
+            .title_bars()
+            .iter()
+            .map(|bar| {
+                serde_json::json!({
+                    "window": bar.window_id.0,
+                    "rect": [bar.rect.x, bar.rect.y, bar.rect.width, bar.rect.height],
+                    "active": bar.active,
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "windows": self.windows.len(),
+            "workspace": self.current_workspace,
+            "layout": self.layout_manager.get_current_layout(),
+            "layout_cycle": self.layout_manager.cycle_names(),
+            "auto_layout": self.layout_manager.rules_active(self.current_workspace),
+            "focused": self.get_focused_window_id().map(|id| id.0),
+            "monocle": monocle,
+            "inner_gap": self.config.general.inner_gap,
+            "outer_gap": self.config.general.outer_gap,
+            "unfit": self
+                .layout_manager
+                .unfit_windows()
+                .iter()
+                .map(|id| id.0)
+                .collect::<Vec<_>>(),
+            "title_bars": title_bars,
+        })
+    }
+
     fn get_focused_window_id(&self) -> Option<WindowId> {
         self.windows
             .values()
@@ -359,17 +980,30 @@ impl WindowManager {
         best_window
     }
 
-    async fn refresh_windows(&mut self) -> Result<()> {
-        let current_windows = self.macos.get_windows().await?;
+    pub async fn refresh_windows(&mut self) -> Result<()> {
+        let current_windows = self.backend.get_windows()?;
         let old_count = self.windows.len();
 
-        // Build a new window map from current windows
+        // Build a new window map from current windows. Known windows keep
+        // their last rect; changes to it arrive as WindowMoved/WindowResized
+        // events so manual resizes can be told apart from our own moves.
         let mut new_windows = HashMap::new();
-        for window in current_windows {
+        for mut window in current_windows {
+            if let Some(existing) = self.windows.get(&window.id) {
+                window.rect = existing.rect;
+            }
+            self.layout_manager.track_window(&window);
             new_windows.insert(window.id, window);
         }
+        for id in self.windows.keys() {
+            if !new_windows.contains_key(id) {
+                self.layout_manager.forget_window(*id);
+            }
+        }
 
         // Replace the old window map with the new one
+        self.requested_rects
+            .retain(|id, _| new_windows.contains_key(id));
         self.windows = new_windows;
         
         let new_count = self.windows.len();
@@ -382,21 +1016,34 @@ impl WindowManager {
         Ok(())
     }
 
-    async fn apply_layout(&mut self) -> Result<()> {
+    pub async fn apply_layout(&mut self) -> Result<()> {
         let workspace_windows: Vec<&Window> = self
             .windows
             .values()
             .filter(|w| w.workspace_id == self.current_workspace && !w.is_minimized)
             .collect();
 
+        // Pick the workspace's layout even when it is empty, so status
+        // reports the right one after switching workspaces
+        let screen_rect = self.backend.get_screen_rect()?;
+        if self.layout_manager.apply_rules(
+            self.current_workspace,
+            workspace_windows.len(),
+            &screen_rect,
+        ) {
+            info!(
+                "Workspace {} uses layout {}",
+                self.current_workspace,
+                self.layout_manager.get_current_layout()
+            );
+        }
+
         if workspace_windows.is_empty() {
             debug!("No windows to layout");
             return Ok(());
         }
         
         debug!("Applying layout to {} windows using {:?}", workspace_windows.len(), self.layout_manager.get_current_layout());
-
-        let screen_rect = self.macos.get_screen_rect().await?;
         let layouts = self.layout_manager.compute_layout(
             &workspace_windows,
             screen_rect,
@@ -405,13 +1052,49 @@ impl WindowManager {
 
         for (window_id, rect) in layouts {
             debug!("Applying layout: moving window {:?} to {:?}", window_id, rect);
-            self.macos.move_window(window_id, rect).await?;
+            self.backend.move_window(window_id, rect)?;
+            self.requested_rects.insert(window_id, rect);
             // Update our internal window state
             if let Some(window) = self.windows.get_mut(&window_id) {
                 window.rect = rect;
             }
         }
 
+        for window_id in self.layout_manager.unfit_windows() {
+            warn!(
+                "Window {:?} is below its minimum size in the current layout",
+                window_id
+            );
+        }
+
+        // Raise the window the layout wants in front if focus is elsewhere
+        if let Some(raised_id) = self.layout_manager.raised_window() {
+            if self.get_focused_window_id() != Some(raised_id) {
+                self.backend.focus_window(raised_id)?;
+            }
+        }
+
         Ok(())
     }
 }
+
+/// Whether two rects have the same size, give or take a pixel of rounding.
+fn same_size(a: &Rect, b: &Rect) -> bool {
+    (a.width - b.width).abs() <= 1.0 && (a.height - b.height).abs() <= 1.0
+}
+
+fn same_rect(a: &Rect, b: &Rect) -> bool {
+    same_size(a, b) && (a.x - b.x).abs() <= 1.0 && (a.y - b.y).abs() <= 1.0
+}
+
+/// Tell an IPC client how a command went, then pass the result on.
+fn answer(reply: Option<Reply>, result: Result<()>) -> Result<()> {
+    if let Some(reply) = reply {
+        let answer = match result {
+            Ok(()) => serde_json::json!({}),
+            Err(ref e) => serde_json::json!({ "error": format!("{:#}", e) }),
+        };
+        let _ = reply.send(answer);
+    }
+    result
+}
@@ -0,0 +1,111 @@
+mod common;
+
+use common::{open, rect, run, send, start, window};
+use skew::hotkeys::Direction;
+use skew::layout::{MirrorAxis, Rotation};
+use skew::window_manager::Command;
+use skew::window_manager::WindowEvent::{WindowCreated, WindowFocused, WorkspaceChanged};
+use skew::{Rect, WindowId};
+
+#[tokio::test]
+async fn focused_new_window_splits_the_last_focused_tile() {
+    let (mut manager, backend) = start(common::config()).await;
+    open(&mut manager, &backend, &[1, 2]).await;
+    send(&mut manager, &backend, WindowFocused(WindowId(1))).await;
+
+    // New windows arrive already focused, before they are in the tree
+    let mut new_window = window(3);
+    new_window.is_focused = true;
+    send(&mut manager, &backend, WindowCreated(new_window)).await;
+
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 960.0, 540.0));
+    assert_eq!(rect(&backend, 3), Rect::new(0.0, 540.0, 960.0, 540.0));
+    assert_eq!(rect(&backend, 2), Rect::new(960.0, 0.0, 960.0, 1080.0));
+}
+
+#[tokio::test]
+async fn each_workspace_keeps_its_own_tree() {
+    let (mut manager, backend) = start(common::config()).await;
+    open(&mut manager, &backend, &[1, 2]).await;
+    send(&mut manager, &backend, WindowFocused(WindowId(1))).await;
+    run(&mut manager, Command::Resize(Direction::Right, 192.0)).await;
+    assert_eq!(rect(&backend, 1).width, 1152.0);
+
+    send(&mut manager, &backend, WorkspaceChanged(2)).await;
+    let mut other = window(3);
+    other.workspace_id = 2;
+    send(&mut manager, &backend, WindowCreated(other)).await;
+    assert_eq!(rect(&backend, 3), Rect::new(0.0, 0.0, 1920.0, 1080.0));
+
+    send(&mut manager, &backend, WorkspaceChanged(1)).await;
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 1152.0, 1080.0));
+    assert_eq!(rect(&backend, 2), Rect::new(1152.0, 0.0, 768.0, 1080.0));
+}
+
+#[tokio::test]
+async fn fixed_split_modes_keep_one_orientation() {
+    let mut config = common::config();
+    config.layout.split_mode = "fixed_horizontal".to_string();
+    let (mut manager, backend) = start(config).await;
+    open(&mut manager, &backend, &[1, 2, 3]).await;
+    assert_eq!(rect(&backend, 2), Rect::new(960.0, 0.0, 480.0, 1080.0));
+    assert_eq!(rect(&backend, 3), Rect::new(1440.0, 0.0, 480.0, 1080.0));
+
+    let mut config = common::config();
+    config.layout.split_mode = "fixed_vertical".to_string();
+    let (mut manager, backend) = start(config).await;
+    open(&mut manager, &backend, &[1, 2, 3]).await;
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 1920.0, 540.0));
+    assert_eq!(rect(&backend, 3), Rect::new(0.0, 810.0, 1920.0, 270.0));
+}
+
+#[tokio::test]
+async fn toggle_split_flips_the_focused_windows_split() {
+    let (mut manager, backend) = start(common::config()).await;
+    open(&mut manager, &backend, &[1, 2, 3]).await;
+    assert_eq!(rect(&backend, 2), Rect::new(960.0, 0.0, 960.0, 540.0));
+
+    send(&mut manager, &backend, WindowFocused(WindowId(2))).await;
+    run(&mut manager, Command::ToggleSplit).await;
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 960.0, 1080.0));
+    assert_eq!(rect(&backend, 2), Rect::new(960.0, 0.0, 480.0, 1080.0));
+    assert_eq!(rect(&backend, 3), Rect::new(1440.0, 0.0, 480.0, 1080.0));
+}
+
+#[tokio::test]
+async fn rotate_and_mirror_move_every_tile() {
+    let (mut manager, backend) = start(common::config()).await;
+    open(&mut manager, &backend, &[1, 2, 3]).await;
+
+    // Clockwise: the left tile goes to the top, the right column to the
+    // bottom with its top half on the right
+    run(&mut manager, Command::Rotate(Rotation::Deg90)).await;
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 1920.0, 540.0));
+    assert_eq!(rect(&backend, 2), Rect::new(960.0, 540.0, 960.0, 540.0));
+    assert_eq!(rect(&backend, 3), Rect::new(0.0, 540.0, 960.0, 540.0));
+
+    run(&mut manager, Command::Rotate(Rotation::Deg270)).await;
+    run(&mut manager, Command::Mirror(MirrorAxis::Y)).await;
+    assert_eq!(rect(&backend, 1), Rect::new(960.0, 0.0, 960.0, 1080.0));
+    assert_eq!(rect(&backend, 2), Rect::new(0.0, 0.0, 960.0, 540.0));
+    assert_eq!(rect(&backend, 3), Rect::new(0.0, 540.0, 960.0, 540.0));
+
+    run(&mut manager, Command::Mirror(MirrorAxis::X)).await;
+    assert_eq!(rect(&backend, 2), Rect::new(0.0, 540.0, 960.0, 540.0));
+    assert_eq!(rect(&backend, 3), Rect::new(0.0, 0.0, 960.0, 540.0));
+}
+
+#[tokio::test]
+async fn balance_sizes_tiles_by_the_windows_they_hold() {
+    let (mut manager, backend) = start(common::config()).await;
+    open(&mut manager, &backend, &[1, 2, 3]).await;
+    send(&mut manager, &backend, WindowFocused(WindowId(1))).await;
+    run(&mut manager, Command::Resize(Direction::Right, 192.0)).await;
+    assert_eq!(rect(&backend, 1).width, 1152.0);
+
+    // One window on the left against two on the right
+    run(&mut manager, Command::Balance).await;
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 640.0, 1080.0));
+    assert_eq!(rect(&backend, 2), Rect::new(640.0, 0.0, 1280.0, 540.0));
+    assert_eq!(rect(&backend, 3), Rect::new(640.0, 540.0, 1280.0, 540.0));
+}
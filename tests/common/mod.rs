@@ -0,0 +1,82 @@
+//! Helpers shared by the integration tests, which drive a `WindowManager`
+//! on top of a `HeadlessBackend`.
+#![allow(dead_code)]
+
+use skew::backend::HeadlessBackend;
+use skew::window_manager::{Command, WindowEvent};
+use skew::{Config, Rect, Window, WindowId, WindowManager};
+use tokio::sync::{mpsc, oneshot};
+
+/// The headless backend's main display.
+pub const SCREEN: Rect = Rect {
+    x: 0.0,
+    y: 0.0,
+    width: 1920.0,
+    height: 1080.0,
+};
+
+pub fn window(id: u32) -> Window {
+    Window {
+        id: WindowId(id),
+        title: format!("Window {}", id),
+        owner: "Terminal".to_string(),
+        rect: Rect::new(100.0, 100.0, 400.0, 300.0),
+        is_minimized: false,
+        is_focused: false,
+        workspace_id: 1,
+        size_hints: Default::default(),
+    }
+}
+
+/// The default config without gaps, so tiles can be compared exactly.
+pub fn config() -> Config {
+    let mut config = Config::default();
+    config.general.inner_gap = 0.0;
+    config.general.outer_gap = 0.0;
+    config
+}
+
+pub async fn start(config: Config) -> (WindowManager, HeadlessBackend) {
+    let (event_tx, event_rx) = mpsc::channel(1000);
+    let backend = HeadlessBackend::new(event_tx.clone());
+    let manager =
+        WindowManager::with_backend(config, Box::new(backend.clone()), event_tx, event_rx)
+            .await
+            .unwrap();
+    (manager, backend)
+}
+
+/// Replay `event` through the backend and let the manager handle it.
+pub async fn send(manager: &mut WindowManager, backend: &HeadlessBackend, event: WindowEvent) {
+    backend.script_event(event);
+    backend.replay_events();
+    manager.process_pending_events().await.unwrap();
+}
+
+pub async fn open(manager: &mut WindowManager, backend: &HeadlessBackend, ids: &[u32]) {
+    for id in ids {
+        backend.script_event(WindowEvent::WindowCreated(window(*id)));
+    }
+    backend.replay_events();
+    manager.process_pending_events().await.unwrap();
+}
+
+/// Run a command, then handle the events it caused, such as focus changes.
+pub async fn run(manager: &mut WindowManager, command: Command) {
+    manager.handle_command(command).await.unwrap();
+    manager.process_pending_events().await.unwrap();
+}
+
+pub async fn status(manager: &mut WindowManager) -> serde_json::Value {
+    let (reply, response) = oneshot::channel();
+    manager
+        .handle_command(Command::GetStatus(Some(reply)))
+        .await
+        .unwrap();
+    response.await.unwrap()
+}
+
+/// Where the backend has placed a window.
+pub fn rect(backend: &HeadlessBackend, id: u32) -> Rect {
+    backend.window(WindowId(id)).unwrap().rect
+}
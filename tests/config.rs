@@ -0,0 +1,45 @@
+use skew::config::LayoutTemplate;
+use skew::Config;
+
+/// The default config as a file, with its `[general]` gaps replaced.
+fn config_with_gaps(gaps: &[(&str, f64)]) -> String {
+    let mut table = toml::Table::try_from(Config::default()).unwrap();
+    let general = table["general"].as_table_mut().unwrap();
+    general.remove("inner_gap");
+    general.remove("outer_gap");
+    for (key, gap) in gaps {
+        general.insert(key.to_string(), toml::Value::Float(*gap));
+    }
+    toml::to_string(&table).unwrap()
+}
+
+#[test]
+fn a_legacy_gap_sets_both_gaps() {
+    let config = Config::from_toml(&config_with_gaps(&[("gap", 4.0)])).unwrap();
+    assert_eq!(config.general.inner_gap, 4.0);
+    assert_eq!(config.general.outer_gap, 4.0);
+}
+
+#[test]
+fn explicit_gaps_win_over_a_legacy_gap() {
+    let content = config_with_gaps(&[("gap", 4.0), ("outer_gap", 0.0)]);
+    let config = Config::from_toml(&content).unwrap();
+    assert_eq!(config.general.inner_gap, 4.0);
+    assert_eq!(config.general.outer_gap, 0.0);
+}
+
+#[test]
+fn templates_cannot_take_built_in_names() {
+    for name in ["grid", "Fullscreen", "editor"] {
+        let mut config = Config::default();
+        config.layout.templates.push(LayoutTemplate {
+            name: name.to_string(),
+            rows: vec![1.0],
+            columns: vec![1.0],
+            areas: vec!["main".to_string()],
+            slots: vec!["main".to_string()],
+            overflow: "stack".to_string(),
+        });
+        assert_eq!(config.validate().is_ok(), name == "editor", "{}", name);
+    }
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::{open, rect, run, send, start, status};
+use skew::layout::ContainerStyle;
+use skew::window_manager::Command;
+use skew::window_manager::WindowEvent::{MouseReleased, WindowFocused, WindowMoved};
+use skew::{Config, Rect, WindowId};
+
+#[tokio::test]
+async fn status_reports_title_bars() {
+    let mut config = common::config();
+    config.layout.title_bar_height = 20.0;
+    let (mut manager, backend) = start(config).await;
+    open(&mut manager, &backend, &[1, 2]).await;
+    send(&mut manager, &backend, WindowFocused(WindowId(1))).await;
+
+    let tabbed = Some(ContainerStyle::Tabbed);
+    run(&mut manager, Command::SetContainerStyle(tabbed)).await;
+    let bars = status(&mut manager).await["title_bars"].clone();
+    assert_eq!(bars[0]["rect"], serde_json::json!([0.0, 0.0, 960.0, 20.0]));
+    assert_eq!(
+        bars[1]["rect"],
+        serde_json::json!([960.0, 0.0, 960.0, 20.0])
+    );
+    assert_eq!(bars[0]["active"], true);
+    assert_eq!(bars[1]["active"], false);
+}
+
+#[tokio::test]
+async fn parked_windows_are_not_dragged() {
+    let (mut manager, backend) = start(common::config()).await;
+    open(&mut manager, &backend, &[1, 2, 3]).await;
+    let tiles: Vec<Rect> = [1, 2, 3].map(|id| rect(&backend, id)).to_vec();
+
+    send(&mut manager, &backend, WindowFocused(WindowId(1))).await;
+    let tabbed = Some(ContainerStyle::Tabbed);
+    run(&mut manager, Command::SetContainerStyle(tabbed)).await;
+    let (hidden, shown) = [1, 2, 3]
+        .into_iter()
+        .partition::<Vec<u32>, _>(|id| rect(&backend, *id).x == 1919.0);
+    assert!(!hidden.is_empty());
+    let before: Vec<Rect> = shown.iter().map(|id| rect(&backend, *id)).collect();
+
+    // The app pulls its parked window back on screen over another tile
+    let parked = rect(&backend, hidden[0]);
+    let target = tiles[0];
+    let pulled = Rect::new(target.x, target.y, parked.width, parked.height);
+    send(
+        &mut manager,
+        &backend,
+        WindowMoved(WindowId(hidden[0]), pulled),
+    )
+    .await;
+    let (x, y) = target.center();
+    send(&mut manager, &backend, MouseReleased { x, y }).await;
+
+    let after: Vec<Rect> = shown.iter().map(|id| rect(&backend, *id)).collect();
+    assert_eq!(after, before);
+}
+
+#[test]
+fn title_bar_height_must_be_a_number() {
+    for height in [f64::NAN, f64::INFINITY, -1.0] {
+        let mut config = Config::default();
+        config.layout.title_bar_height = height;
+        assert!(config.validate().is_err(), "accepted {}", height);
+    }
+}
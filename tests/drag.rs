@@ -0,0 +1,48 @@
+mod common;
+
+use common::{open, rect, run, send, start};
+use skew::window_manager::Command;
+use skew::window_manager::WindowEvent::{MouseReleased, WindowMoved};
+use skew::{Rect, WindowId};
+
+#[tokio::test]
+async fn windows_are_dropped_when_the_drag_ends() {
+    let (mut manager, backend) = start(common::config()).await;
+    open(&mut manager, &backend, &[1, 2]).await;
+
+    // Positions reported mid-drag leave the tiles alone
+    let dragged = Rect::new(500.0, 0.0, 960.0, 1080.0);
+    send(&mut manager, &backend, WindowMoved(WindowId(1), dragged)).await;
+    assert_eq!(rect(&backend, 2), Rect::new(960.0, 0.0, 960.0, 1080.0));
+
+    // The window's centre is near the edge of tile 2, but the cursor is
+    // over its middle, so the two swap
+    let release = MouseReleased {
+        x: 1440.0,
+        y: 540.0,
+    };
+    send(&mut manager, &backend, release).await;
+    assert_eq!(rect(&backend, 2), Rect::new(0.0, 0.0, 960.0, 1080.0));
+    assert_eq!(rect(&backend, 1), Rect::new(960.0, 0.0, 960.0, 1080.0));
+}
+
+#[tokio::test]
+async fn a_drop_is_undone_in_one_step() {
+    let (mut manager, backend) = start(common::config()).await;
+    open(&mut manager, &backend, &[1, 2]).await;
+
+    for x in [300.0, 600.0, 900.0] {
+        let dragged = Rect::new(x, 0.0, 960.0, 1080.0);
+        send(&mut manager, &backend, WindowMoved(WindowId(1), dragged)).await;
+    }
+    let release = MouseReleased {
+        x: 1440.0,
+        y: 540.0,
+    };
+    send(&mut manager, &backend, release).await;
+    assert_eq!(rect(&backend, 1).x, 960.0);
+
+    run(&mut manager, Command::LayoutUndo).await;
+    assert_eq!(rect(&backend, 1).x, 0.0);
+    assert_eq!(rect(&backend, 2).x, 960.0);
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::{open, rect, run, start};
+use skew::hotkeys::HotkeyManager;
+use skew::window_manager::Command;
+use skew::{Config, Rect};
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn gap_commands_change_both_gaps() {
+    let (mut manager, backend) = start(common::config()).await;
+    open(&mut manager, &backend, &[1, 2]).await;
+
+    run(&mut manager, Command::SetGap(10.0)).await;
+    assert_eq!(rect(&backend, 1), Rect::new(10.0, 10.0, 945.0, 1060.0));
+
+    run(&mut manager, Command::AdjustGap(-10.0)).await;
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 960.0, 1080.0));
+}
+
+#[tokio::test]
+async fn gap_amounts_must_be_numbers() {
+    for action in ["gap_inc:nan", "gap_dec:inf", "gap_set:nan", "gap_set:-inf"] {
+        let mut config = common::config();
+        config
+            .hotkeys
+            .bindings
+            .insert("alt+g".to_string(), action.to_string());
+        let (command_tx, _command_rx) = mpsc::channel(1);
+        let hotkeys = HotkeyManager::new(&config.hotkeys, command_tx).unwrap();
+        let result = hotkeys.simulate_hotkey("alt+g").await;
+        assert!(result.is_err(), "accepted {}", action);
+    }
+}
+
+#[test]
+fn padding_must_be_a_number() {
+    for padding in [f64::NAN, f64::INFINITY, -1.0] {
+        let mut config = Config::default();
+        config.general.padding.top = padding;
+        assert!(config.validate().is_err(), "accepted {}", padding);
+    }
+}
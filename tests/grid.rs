@@ -0,0 +1,37 @@
+mod common;
+
+use common::{open, rect, start};
+use skew::Rect;
+
+async fn grid_rects(rows: usize, columns: usize, order: &str) -> Vec<Rect> {
+    let mut config = common::config();
+    config.layout.default_layout = "grid".to_string();
+    config.layout.grid_rows = rows;
+    config.layout.grid_columns = columns;
+    config.layout.grid_order = order.to_string();
+    let (mut manager, backend) = start(config).await;
+    open(&mut manager, &backend, &[1, 2, 3, 4]).await;
+    [1, 2, 3, 4].map(|id| rect(&backend, id)).to_vec()
+}
+
+#[tokio::test]
+async fn a_fixed_count_across_the_fill_leaves_no_empty_lines() {
+    let quarters = [
+        Rect::new(0.0, 0.0, 960.0, 540.0),
+        Rect::new(960.0, 0.0, 960.0, 540.0),
+        Rect::new(0.0, 540.0, 960.0, 540.0),
+        Rect::new(960.0, 540.0, 960.0, 540.0),
+    ];
+    assert_eq!(grid_rects(3, 0, "row_major").await, quarters);
+
+    let by_column = [0, 2, 1, 3].map(|i| quarters[i]).to_vec();
+    assert_eq!(grid_rects(0, 3, "column_major").await, by_column);
+}
+
+#[tokio::test]
+async fn a_fixed_count_along_the_fill_sets_the_line_length() {
+    let rects = grid_rects(3, 0, "column_major").await;
+    assert_eq!(rects[0], Rect::new(0.0, 0.0, 960.0, 360.0));
+    assert_eq!(rects[2], Rect::new(0.0, 720.0, 960.0, 360.0));
+    assert_eq!(rects[3], Rect::new(960.0, 0.0, 960.0, 360.0));
+}
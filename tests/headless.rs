@@ -0,0 +1,92 @@
+mod common;
+
+use common::{open, rect, run, send, start, status, SCREEN};
+use skew::hotkeys::Direction;
+use skew::window_manager::Command;
+use skew::window_manager::WindowEvent::{WindowFocused, WindowMinimized};
+use skew::{Rect, WindowId};
+
+#[tokio::test]
+async fn new_windows_are_tiled() {
+    let (mut manager, backend) = start(common::config()).await;
+
+    open(&mut manager, &backend, &[1]).await;
+    assert_eq!(rect(&backend, 1), SCREEN);
+
+    open(&mut manager, &backend, &[2]).await;
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 960.0, 1080.0));
+    assert_eq!(rect(&backend, 2), Rect::new(960.0, 0.0, 960.0, 1080.0));
+    assert_eq!(status(&mut manager).await["windows"], 2);
+}
+
+#[tokio::test]
+async fn gaps_inset_tiles() {
+    let mut config = common::config();
+    config.general.inner_gap = 10.0;
+    config.general.outer_gap = 20.0;
+    let (mut manager, backend) = start(config).await;
+
+    open(&mut manager, &backend, &[1, 2]).await;
+    assert_eq!(rect(&backend, 1), Rect::new(20.0, 20.0, 935.0, 1040.0));
+    assert_eq!(rect(&backend, 2), Rect::new(965.0, 20.0, 935.0, 1040.0));
+}
+
+#[tokio::test]
+async fn closed_and_minimized_windows_give_up_their_tile() {
+    let (mut manager, backend) = start(common::config()).await;
+    open(&mut manager, &backend, &[1, 2, 3]).await;
+
+    send(&mut manager, &backend, WindowMinimized(WindowId(3))).await;
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 960.0, 1080.0));
+    assert_eq!(rect(&backend, 2), Rect::new(960.0, 0.0, 960.0, 1080.0));
+
+    send(&mut manager, &backend, WindowFocused(WindowId(2))).await;
+    run(&mut manager, Command::CloseFocusedWindow).await;
+    assert!(backend.window(WindowId(2)).is_none());
+    assert_eq!(rect(&backend, 1), SCREEN);
+}
+
+#[tokio::test]
+async fn focus_and_move_by_direction() {
+    let (mut manager, backend) = start(common::config()).await;
+    open(&mut manager, &backend, &[1, 2]).await;
+    send(&mut manager, &backend, WindowFocused(WindowId(1))).await;
+
+    run(&mut manager, Command::FocusDirection(Direction::Right)).await;
+    assert_eq!(status(&mut manager).await["focused"], 2);
+
+    run(&mut manager, Command::MoveDirection(Direction::Left)).await;
+    assert_eq!(rect(&backend, 2), Rect::new(0.0, 0.0, 960.0, 1080.0));
+    assert_eq!(rect(&backend, 1), Rect::new(960.0, 0.0, 960.0, 1080.0));
+}
+
+#[tokio::test]
+async fn layout_commands_reapply_rects() {
+    let (mut manager, backend) = start(common::config()).await;
+    open(&mut manager, &backend, &[1, 2, 3]).await;
+
+    run(&mut manager, Command::SetLayout("column".to_string(), None)).await;
+    assert_eq!(status(&mut manager).await["layout"], "column");
+    for (i, id) in [1, 2, 3].into_iter().enumerate() {
+        assert_eq!(
+            rect(&backend, id),
+            Rect::new(i as f64 * 640.0, 0.0, 640.0, 1080.0)
+        );
+    }
+
+    run(&mut manager, Command::SwapWindows(WindowId(1), WindowId(3))).await;
+    assert_eq!(rect(&backend, 3).x, 0.0);
+    assert_eq!(rect(&backend, 1).x, 1280.0);
+}
+
+#[tokio::test]
+async fn unknown_layouts_are_answered_with_an_error() {
+    let (mut manager, _backend) = start(common::config()).await;
+    let (reply, answer) = tokio::sync::oneshot::channel();
+    let command = Command::SetLayout("gird".to_string(), Some(reply));
+    assert!(manager.handle_command(command).await.is_err());
+
+    let answer = answer.await.unwrap();
+    assert!(answer["error"].as_str().unwrap().contains("gird"));
+    assert_eq!(status(&mut manager).await["layout"], "bsp");
+}
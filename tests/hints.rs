@@ -0,0 +1,75 @@
+mod common;
+
+use common::{rect, send, start, status, window};
+use skew::window_manager::WindowEvent::WindowCreated;
+use skew::{Rect, SizeHints};
+
+async fn open_with_hints(
+    manager: &mut skew::WindowManager,
+    backend: &skew::backend::HeadlessBackend,
+    hints: &[SizeHints],
+) {
+    for (i, size_hints) in hints.iter().enumerate() {
+        let mut window = window(i as u32 + 1);
+        window.size_hints = *size_hints;
+        send(manager, backend, WindowCreated(window)).await;
+    }
+}
+
+fn min_width(width: f64) -> SizeHints {
+    SizeHints {
+        min_width: Some(width),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn splits_make_room_for_a_minimum_size() {
+    let (mut manager, backend) = start(common::config()).await;
+    open_with_hints(
+        &mut manager,
+        &backend,
+        &[min_width(1200.0), SizeHints::default()],
+    )
+    .await;
+
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 1200.0, 1080.0));
+    assert_eq!(rect(&backend, 2), Rect::new(1200.0, 0.0, 720.0, 1080.0));
+    assert_eq!(status(&mut manager).await["unfit"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn a_maximum_size_gives_the_rest_to_its_neighbour() {
+    let (mut manager, backend) = start(common::config()).await;
+    let small = SizeHints {
+        max_width: Some(400.0),
+        max_height: Some(300.0),
+        ..Default::default()
+    };
+    open_with_hints(&mut manager, &backend, &[SizeHints::default(), small]).await;
+
+    // Too short for its tile, the window is centred in it
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 1520.0, 1080.0));
+    assert_eq!(rect(&backend, 2), Rect::new(1520.0, 390.0, 400.0, 300.0));
+}
+
+#[tokio::test]
+async fn minimums_that_cannot_fit_are_reported() {
+    let (mut manager, backend) = start(common::config()).await;
+    open_with_hints(
+        &mut manager,
+        &backend,
+        &[min_width(1200.0), min_width(1200.0)],
+    )
+    .await;
+
+    let unfit = status(&mut manager).await["unfit"].clone();
+    let mut unfit: Vec<u64> = unfit
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|id| id.as_u64())
+        .collect();
+    unfit.sort();
+    assert_eq!(unfit, [1, 2]);
+}
@@ -0,0 +1,28 @@
+#![cfg(feature = "scripting")]
+
+mod common;
+
+use common::{open, rect, start};
+use skew::Rect;
+
+#[tokio::test]
+async fn a_lua_layout_that_never_returns_falls_back_to_bsp() {
+    let plugin_dir = std::env::temp_dir().join(format!("skew-lua-{}", std::process::id()));
+    std::fs::create_dir_all(&plugin_dir).unwrap();
+    std::fs::write(
+        plugin_dir.join("spin.lua"),
+        "register_layout('spin', function(screen, windows, gaps) while true do end end)",
+    )
+    .unwrap();
+
+    let mut config = common::config();
+    config.plugins.enabled = vec!["spin".to_string()];
+    config.plugins.plugin_dir = plugin_dir.to_string_lossy().into_owned();
+    config.layout.default_layout = "spin".to_string();
+    let (mut manager, backend) = start(config).await;
+    open(&mut manager, &backend, &[1, 2]).await;
+    std::fs::remove_dir_all(&plugin_dir).unwrap();
+
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 960.0, 1080.0));
+    assert_eq!(rect(&backend, 2), Rect::new(960.0, 0.0, 960.0, 1080.0));
+}
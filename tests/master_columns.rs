@@ -0,0 +1,40 @@
+mod common;
+
+use common::{open, rect, start};
+use skew::{Config, Rect};
+
+#[tokio::test]
+async fn centered_master_alternates_the_stack_around_the_master() {
+    let mut config = common::config();
+    config.layout.default_layout = "centered_master".to_string();
+    let (mut manager, backend) = start(config).await;
+    open(&mut manager, &backend, &[1, 2, 3, 4]).await;
+
+    assert_eq!(rect(&backend, 1), Rect::new(480.0, 0.0, 960.0, 1080.0));
+    assert_eq!(rect(&backend, 2), Rect::new(1440.0, 0.0, 480.0, 540.0));
+    assert_eq!(rect(&backend, 3), Rect::new(0.0, 0.0, 480.0, 1080.0));
+    assert_eq!(rect(&backend, 4), Rect::new(1440.0, 540.0, 480.0, 540.0));
+}
+
+#[tokio::test]
+async fn three_column_sizes_columns_by_their_ratios() {
+    let mut config = common::config();
+    config.layout.default_layout = "three_column".to_string();
+    config.layout.three_column_ratios = vec![2.0, 1.0, 1.0];
+    let (mut manager, backend) = start(config).await;
+    open(&mut manager, &backend, &[1, 2, 3, 4]).await;
+
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 960.0, 1080.0));
+    assert_eq!(rect(&backend, 2), Rect::new(960.0, 0.0, 480.0, 540.0));
+    assert_eq!(rect(&backend, 3), Rect::new(960.0, 540.0, 480.0, 540.0));
+    assert_eq!(rect(&backend, 4), Rect::new(1440.0, 0.0, 480.0, 1080.0));
+}
+
+#[test]
+fn three_column_ratios_must_be_positive_numbers() {
+    for ratio in [f64::NAN, f64::INFINITY, 0.0] {
+        let mut config = Config::default();
+        config.layout.three_column_ratios = vec![1.0, ratio, 1.0];
+        assert!(config.validate().is_err(), "accepted {}", ratio);
+    }
+}
@@ -0,0 +1,26 @@
+mod common;
+
+use common::{open, run, start, status};
+use skew::window_manager::Command;
+
+#[tokio::test]
+async fn status_reports_the_shown_windows_position() {
+    let mut config = common::config();
+    config.layout.default_layout = "monocle".to_string();
+    let (mut manager, backend) = start(config).await;
+    open(&mut manager, &backend, &[1, 2, 3]).await;
+    assert_eq!(status(&mut manager).await["monocle"], "1/3");
+
+    run(&mut manager, Command::CycleWindow(true)).await;
+    let reply = status(&mut manager).await;
+    assert_eq!(reply["monocle"], "2/3");
+    assert_eq!(reply["focused"], 2);
+
+    // Stepping back from the first window wraps to the last
+    run(&mut manager, Command::CycleWindow(false)).await;
+    run(&mut manager, Command::CycleWindow(false)).await;
+    assert_eq!(status(&mut manager).await["monocle"], "3/3");
+
+    run(&mut manager, Command::SetLayout("bsp".to_string(), None)).await;
+    assert!(status(&mut manager).await["monocle"].is_null());
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::{open, rect, run, send, start};
+use skew::hotkeys::HotkeyManager;
+use skew::window_manager::Command;
+use skew::window_manager::WindowEvent::WindowFocused;
+use skew::{Config, Rect, WindowId};
+use tokio::sync::mpsc;
+
+fn paper() -> Config {
+    let mut config = common::config();
+    config.layout.default_layout = "paper".to_string();
+    config
+}
+
+#[tokio::test]
+async fn the_strip_scrolls_to_the_focused_column() {
+    let (mut manager, backend) = start(paper()).await;
+    open(&mut manager, &backend, &[1, 2, 3]).await;
+    assert_eq!(rect(&backend, 3), Rect::new(1920.0, 0.0, 960.0, 1080.0));
+
+    send(&mut manager, &backend, WindowFocused(WindowId(3))).await;
+    assert_eq!(rect(&backend, 1), Rect::new(-960.0, 0.0, 960.0, 1080.0));
+    assert_eq!(rect(&backend, 3), Rect::new(960.0, 0.0, 960.0, 1080.0));
+}
+
+#[tokio::test]
+async fn column_width_steps_widen_the_focused_column() {
+    let (mut manager, backend) = start(paper()).await;
+    open(&mut manager, &backend, &[1, 2]).await;
+    send(&mut manager, &backend, WindowFocused(WindowId(1))).await;
+
+    run(&mut manager, Command::AdjustColumnWidth(0.25)).await;
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 1440.0, 1080.0));
+    assert_eq!(rect(&backend, 2), Rect::new(1440.0, 0.0, 960.0, 1080.0));
+}
+
+#[tokio::test]
+async fn column_widths_must_be_numbers() {
+    for step in ["nan", "inf", "wide"] {
+        let mut config = common::config();
+        config
+            .hotkeys
+            .bindings
+            .insert("alt+w".to_string(), format!("column_wider:{}", step));
+        let (command_tx, _command_rx) = mpsc::channel(1);
+        let hotkeys = HotkeyManager::new(&config.hotkeys, command_tx).unwrap();
+        let result = hotkeys.simulate_hotkey("alt+w").await;
+        assert!(result.is_err(), "accepted {}", step);
+    }
+
+    for width in [f64::NAN, f64::INFINITY] {
+        let mut config = Config::default();
+        config.layout.column_width = width;
+        assert!(config.validate().is_err(), "accepted {}", width);
+    }
+}
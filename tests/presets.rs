@@ -0,0 +1,101 @@
+mod common;
+
+use common::{open, rect, start};
+use skew::preset::LayoutPreset;
+use skew::window_manager::Command;
+use skew::{Config, WindowManager};
+use std::path::{Path, PathBuf};
+use tokio::sync::oneshot;
+
+fn preset_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("skew-presets-{}-{}", test, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn config(dir: &Path) -> Config {
+    let mut config = common::config();
+    config.layout.preset_dir = dir.to_string_lossy().into_owned();
+    config
+}
+
+/// Send a preset command the way IPC does and return the answer.
+async fn ask(
+    manager: &mut WindowManager,
+    command: impl FnOnce(skew::window_manager::Reply) -> Command,
+) -> serde_json::Value {
+    let (reply, answer) = oneshot::channel();
+    let _ = manager.handle_command(command(reply)).await;
+    answer.await.unwrap()
+}
+
+#[tokio::test]
+async fn preset_errors_are_answered() {
+    let dir = preset_dir("errors");
+    let (mut manager, _backend) = start(config(&dir)).await;
+
+    let answer = ask(&mut manager, |reply| {
+        Command::ApplyPreset("missing".to_string(), Some(reply))
+    })
+    .await;
+    assert!(answer["error"].is_string(), "{}", answer);
+
+    let answer = ask(&mut manager, |reply| {
+        Command::SavePreset("saved".to_string(), Some(reply))
+    })
+    .await;
+    assert_eq!(answer, serde_json::json!({}));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn presets_reject_ratios_that_are_not_numbers() {
+    for setting in [
+        "split_ratio = nan",
+        "three_column_ratios = [1.0, nan, 1.0]",
+        "three_column_ratios = [1.0, inf, 1.0]",
+        "[bsp.split]\nhorizontal = true\nratio = nan\nfirst.window.owner = \"a\"\nsecond.window.owner = \"b\"",
+    ] {
+        let content = format!("layout = \"bsp\"\n{}", setting);
+        let preset: LayoutPreset = toml::from_str(&content).unwrap();
+        assert!(preset.validate().is_err(), "accepted {}", setting);
+    }
+}
+
+#[tokio::test]
+async fn slots_claim_windows_in_tiling_order() {
+    let dir = preset_dir("order");
+    std::fs::write(
+        dir.join("first.toml"),
+        "layout = \"stack\"\n[[slots]]\nowner = \"Terminal\"\n",
+    )
+    .unwrap();
+    let (mut manager, backend) = start(config(&dir)).await;
+    open(&mut manager, &backend, &[1, 2, 3, 4, 5, 6, 7, 8]).await;
+
+    common::run(
+        &mut manager,
+        Command::ApplyPreset("first".to_string(), None),
+    )
+    .await;
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(rect(&backend, 1).x, 0.0);
+    assert_eq!(rect(&backend, 1).height, 1080.0);
+}
+
+#[tokio::test]
+async fn a_leading_tilde_is_the_home_directory() {
+    let home = preset_dir("home");
+    std::env::set_var("HOME", &home);
+    let mut config = common::config();
+    config.layout.preset_dir = "~/presets".to_string();
+    let (mut manager, _backend) = start(config).await;
+
+    let answer = ask(&mut manager, |reply| {
+        Command::SavePreset("saved".to_string(), Some(reply))
+    })
+    .await;
+    assert_eq!(answer, serde_json::json!({}));
+    assert!(home.join("presets").join("saved.toml").exists());
+    std::fs::remove_dir_all(&home).unwrap();
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::{window, SCREEN};
+use skew::layout::{Layout, LayoutManager};
+use skew::{Rect, Result, Window, WindowId};
+use std::collections::HashMap;
+
+/// Tiles every window full screen, as long as the manager still reports it
+/// as the current layout while it runs.
+struct Inspector;
+
+impl Layout for Inspector {
+    fn name(&self) -> &str {
+        "inspector"
+    }
+
+    fn compute(
+        &mut self,
+        manager: &mut LayoutManager,
+        windows: &[&Window],
+        area: Rect,
+        _gap: f64,
+    ) -> Result<HashMap<WindowId, Rect>> {
+        match manager.get_current_layout() {
+            "inspector" => Ok(windows.iter().map(|w| (w.id, area)).collect()),
+            other => Err(anyhow::anyhow!("current layout is {}", other)),
+        }
+    }
+}
+
+#[test]
+fn a_running_layout_is_still_the_current_one() {
+    let config = common::config();
+    let mut manager = LayoutManager::new(&config.layout);
+    manager.register(Box::new(Inspector)).unwrap();
+    assert!(manager.set_layout("inspector"));
+
+    let windows = [window(1), window(2)];
+    let windows: Vec<&Window> = windows.iter().collect();
+    let rects = manager.compute_layout(&windows, SCREEN, &config.general);
+    // BSP, the fallback for a failed layout, would split the screen
+    assert_eq!(rects[&WindowId(1)], SCREEN);
+    assert_eq!(rects[&WindowId(2)], SCREEN);
+}
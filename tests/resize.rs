@@ -0,0 +1,88 @@
+mod common;
+
+use common::{open, rect, run, send, start, window};
+use skew::hotkeys::{Direction, HotkeyManager};
+use skew::window_manager::Command;
+use skew::window_manager::WindowEvent::{WindowCreated, WindowFocused, WindowResized};
+use skew::{Rect, WindowId};
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn resize_is_measured_against_the_padded_area() {
+    let mut config = common::config();
+    config.layout.default_layout = "stack".to_string();
+    config.general.padding.left = 200.0;
+    let (mut manager, backend) = start(config).await;
+    open(&mut manager, &backend, &[1, 2]).await;
+    send(&mut manager, &backend, WindowFocused(WindowId(1))).await;
+
+    // 172px is a tenth of the 1720px left after padding
+    run(&mut manager, Command::Resize(Direction::Right, 172.0)).await;
+    assert_eq!(rect(&backend, 1), Rect::new(200.0, 0.0, 1032.0, 1080.0));
+}
+
+#[tokio::test]
+async fn centered_master_grows_on_both_sides() {
+    let mut config = common::config();
+    config.layout.default_layout = "centered_master".to_string();
+    let (mut manager, backend) = start(config).await;
+    open(&mut manager, &backend, &[1, 2, 3]).await;
+    assert_eq!(rect(&backend, 1), Rect::new(480.0, 0.0, 960.0, 1080.0));
+
+    run(&mut manager, Command::Resize(Direction::Right, 96.0)).await;
+    assert_eq!(rect(&backend, 1), Rect::new(384.0, 0.0, 1152.0, 1080.0));
+    assert_eq!(rect(&backend, 3), Rect::new(0.0, 0.0, 384.0, 1080.0));
+    assert_eq!(rect(&backend, 2), Rect::new(1536.0, 0.0, 384.0, 1080.0));
+
+    run(&mut manager, Command::Resize(Direction::Left, 96.0)).await;
+    assert_eq!(rect(&backend, 1), Rect::new(480.0, 0.0, 960.0, 1080.0));
+}
+
+#[tokio::test]
+async fn resize_amounts_must_be_positive_numbers() {
+    for amount in ["nan", "inf", "-20", "0"] {
+        let mut config = common::config();
+        config
+            .hotkeys
+            .bindings
+            .insert("alt+r".to_string(), format!("resize_right:{}", amount));
+        let (command_tx, _command_rx) = mpsc::channel(1);
+        let hotkeys = HotkeyManager::new(&config.hotkeys, command_tx).unwrap();
+        let result = hotkeys.simulate_hotkey("alt+r").await;
+        assert!(result.is_err(), "accepted {}", amount);
+    }
+}
+
+#[tokio::test]
+async fn sizes_within_the_hints_are_not_manual_resizes() {
+    let mut config = common::config();
+    config.layout.default_layout = "stack".to_string();
+    let (mut manager, backend) = start(config).await;
+    for (id, min_width) in [(1, 1000.0), (2, 1200.0)] {
+        let mut window = window(id);
+        window.size_hints.min_width = Some(min_width);
+        send(&mut manager, &backend, WindowCreated(window)).await;
+    }
+    let master = rect(&backend, 1);
+
+    // The app kept its minimum width; the poll reports what it did
+    let clamped = rect(&backend, 2);
+    send(&mut manager, &backend, WindowResized(WindowId(2), clamped)).await;
+    assert_eq!(manager.layout_manager().get_split_ratio(), 0.5);
+    assert_eq!(rect(&backend, 1), master);
+}
+
+#[tokio::test]
+async fn a_second_drag_to_the_same_size_is_a_manual_resize() {
+    let (mut manager, backend) = start(common::config()).await;
+    open(&mut manager, &backend, &[1, 2]).await;
+
+    let narrow = Rect::new(0.0, 0.0, 800.0, 1080.0);
+    send(&mut manager, &backend, WindowResized(WindowId(1), narrow)).await;
+    assert_eq!(rect(&backend, 2), Rect::new(800.0, 0.0, 1120.0, 1080.0));
+
+    run(&mut manager, Command::Balance).await;
+    assert_eq!(rect(&backend, 2), Rect::new(960.0, 0.0, 960.0, 1080.0));
+    send(&mut manager, &backend, WindowResized(WindowId(1), narrow)).await;
+    assert_eq!(rect(&backend, 2), Rect::new(800.0, 0.0, 1120.0, 1080.0));
+}
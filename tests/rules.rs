@@ -0,0 +1,49 @@
+mod common;
+
+use common::{open, run, send, start, status};
+use skew::config::LayoutRule;
+use skew::window_manager::Command;
+use skew::window_manager::WindowEvent::WorkspaceChanged;
+
+fn rule(layout: &str) -> LayoutRule {
+    LayoutRule {
+        layout: layout.to_string(),
+        min_windows: None,
+        max_windows: None,
+        min_aspect_ratio: None,
+        max_aspect_ratio: None,
+        workspaces: Vec::new(),
+    }
+}
+
+#[tokio::test]
+async fn a_layout_picked_by_hand_stays_on_its_workspace() {
+    let mut config = common::config();
+    config.layout.rules = vec![LayoutRule {
+        workspaces: vec![2],
+        ..rule("monocle")
+    }];
+    let (mut manager, backend) = start(config).await;
+    open(&mut manager, &backend, &[1, 2]).await;
+    run(&mut manager, Command::SetLayout("grid".to_string(), None)).await;
+
+    send(&mut manager, &backend, WorkspaceChanged(2)).await;
+    assert_eq!(status(&mut manager).await["layout"], "monocle");
+    send(&mut manager, &backend, WorkspaceChanged(1)).await;
+    assert_eq!(status(&mut manager).await["layout"], "grid");
+}
+
+#[tokio::test]
+async fn the_default_layout_returns_when_no_rule_matches() {
+    let mut config = common::config();
+    config.layout.rules = vec![LayoutRule {
+        max_windows: Some(1),
+        ..rule("monocle")
+    }];
+    let (mut manager, backend) = start(config).await;
+
+    open(&mut manager, &backend, &[1]).await;
+    assert_eq!(status(&mut manager).await["layout"], "monocle");
+    open(&mut manager, &backend, &[2]).await;
+    assert_eq!(status(&mut manager).await["layout"], "bsp");
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use common::{open, rect, start};
+use skew::Rect;
+
+#[tokio::test]
+async fn spiral_winds_inwards_clockwise() {
+    let mut config = common::config();
+    config.layout.default_layout = "spiral".to_string();
+    let (mut manager, backend) = start(config).await;
+    open(&mut manager, &backend, &[1, 2, 3, 4, 5]).await;
+
+    // Left, top, right, bottom, then what is left in the middle
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 960.0, 1080.0));
+    assert_eq!(rect(&backend, 2), Rect::new(960.0, 0.0, 960.0, 540.0));
+    assert_eq!(rect(&backend, 3), Rect::new(1440.0, 540.0, 480.0, 540.0));
+    assert_eq!(rect(&backend, 4), Rect::new(960.0, 810.0, 480.0, 270.0));
+    assert_eq!(rect(&backend, 5), Rect::new(960.0, 540.0, 480.0, 270.0));
+}
+
+#[tokio::test]
+async fn dwindle_shrinks_towards_the_bottom_right() {
+    let mut config = common::config();
+    config.layout.default_layout = "dwindle".to_string();
+    let (mut manager, backend) = start(config).await;
+    open(&mut manager, &backend, &[1, 2, 3, 4, 5]).await;
+
+    assert_eq!(rect(&backend, 1), Rect::new(0.0, 0.0, 960.0, 1080.0));
+    assert_eq!(rect(&backend, 2), Rect::new(960.0, 0.0, 960.0, 540.0));
+    assert_eq!(rect(&backend, 3), Rect::new(960.0, 540.0, 480.0, 540.0));
+    assert_eq!(rect(&backend, 4), Rect::new(1440.0, 540.0, 480.0, 270.0));
+    assert_eq!(rect(&backend, 5), Rect::new(1440.0, 810.0, 480.0, 270.0));
+}
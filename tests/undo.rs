@@ -0,0 +1,31 @@
+mod common;
+
+use common::{open, rect, run, send, start, window};
+use skew::window_manager::Command;
+use skew::window_manager::WindowEvent::{WindowCreated, WorkspaceChanged};
+use skew::WindowId;
+
+#[tokio::test]
+async fn undo_only_reverts_the_shown_workspace() {
+    let (mut manager, backend) = start(common::config()).await;
+    open(&mut manager, &backend, &[1, 2]).await;
+    run(&mut manager, Command::SwapWindows(WindowId(1), WindowId(2))).await;
+    assert_eq!(rect(&backend, 1).x, 960.0);
+
+    send(&mut manager, &backend, WorkspaceChanged(2)).await;
+    for id in [3, 4] {
+        let mut other = window(id);
+        other.workspace_id = 2;
+        send(&mut manager, &backend, WindowCreated(other)).await;
+    }
+    run(&mut manager, Command::SwapWindows(WindowId(3), WindowId(4))).await;
+    assert_eq!(rect(&backend, 3).x, 960.0);
+
+    // Both undos step back on workspace 2; the swap on workspace 1 stays
+    run(&mut manager, Command::LayoutUndo).await;
+    run(&mut manager, Command::LayoutUndo).await;
+    assert_eq!(rect(&backend, 3).x, 0.0);
+    send(&mut manager, &backend, WorkspaceChanged(1)).await;
+    assert_eq!(rect(&backend, 1).x, 960.0);
+    assert_eq!(rect(&backend, 2).x, 0.0);
+}
@@ -0,0 +1,39 @@
+mod common;
+
+use common::{open, rect, run, send, start};
+use skew::hotkeys::HotkeyManager;
+use skew::window_manager::Command;
+use skew::window_manager::WindowEvent::WindowFocused;
+use skew::{Rect, WindowId};
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn weights_share_the_stack_in_proportion() {
+    let mut config = common::config();
+    config.layout.default_layout = "stack".to_string();
+    let (mut manager, backend) = start(config).await;
+    open(&mut manager, &backend, &[1, 2, 3]).await;
+    send(&mut manager, &backend, WindowFocused(WindowId(2))).await;
+
+    run(&mut manager, Command::AdjustWeight(1.0)).await;
+    assert_eq!(rect(&backend, 2), Rect::new(960.0, 0.0, 960.0, 720.0));
+    assert_eq!(rect(&backend, 3), Rect::new(960.0, 720.0, 960.0, 360.0));
+
+    run(&mut manager, Command::ResetWeight).await;
+    assert_eq!(rect(&backend, 2), Rect::new(960.0, 0.0, 960.0, 540.0));
+}
+
+#[tokio::test]
+async fn weight_steps_must_be_numbers() {
+    for step in ["nan", "inf", "-inf", "heavy"] {
+        let mut config = common::config();
+        config
+            .hotkeys
+            .bindings
+            .insert("alt+w".to_string(), format!("inc_weight:{}", step));
+        let (command_tx, _command_rx) = mpsc::channel(1);
+        let hotkeys = HotkeyManager::new(&config.hotkeys, command_tx).unwrap();
+        let result = hotkeys.simulate_hotkey("alt+w").await;
+        assert!(result.is_err(), "accepted {}", step);
+    }
+}
@@ -0,0 +1,105 @@
+mod common;
+
+use common::{open, rect, run, send, start};
+use skew::ipc::{IpcClient, IpcServer};
+use skew::window_manager::Command;
+use skew::window_manager::WindowEvent::{WindowDestroyed, WindowFocused};
+use skew::{Config, WindowId, WindowManager};
+use tokio::sync::mpsc;
+
+fn stack() -> Config {
+    let mut config = common::config();
+    config.layout.default_layout = "stack".to_string();
+    config
+}
+
+/// Windows from left to right, then top to bottom.
+fn tiling_order(backend: &skew::backend::HeadlessBackend, ids: &[u32]) -> Vec<u32> {
+    let mut order = ids.to_vec();
+    order.sort_by(|a, b| {
+        let (a, b) = (rect(backend, *a), rect(backend, *b));
+        (a.x, a.y).partial_cmp(&(b.x, b.y)).unwrap()
+    });
+    order
+}
+
+#[tokio::test]
+async fn windows_keep_the_order_they_opened_in() {
+    let (mut manager, backend) = start(stack()).await;
+    open(&mut manager, &backend, &[3, 1, 2]).await;
+    assert_eq!(tiling_order(&backend, &[1, 2, 3]), [3, 1, 2]);
+
+    for _ in 0..3 {
+        manager.refresh_windows().await.unwrap();
+        manager.apply_layout().await.unwrap();
+        assert_eq!(tiling_order(&backend, &[1, 2, 3]), [3, 1, 2]);
+    }
+
+    send(&mut manager, &backend, WindowDestroyed(WindowId(3))).await;
+    assert_eq!(tiling_order(&backend, &[1, 2]), [1, 2]);
+}
+
+#[tokio::test]
+async fn the_order_can_be_changed_by_hand() {
+    let (mut manager, backend) = start(stack()).await;
+    open(&mut manager, &backend, &[1, 2, 3]).await;
+    send(&mut manager, &backend, WindowFocused(WindowId(3))).await;
+
+    run(&mut manager, Command::MoveToFirst).await;
+    assert_eq!(tiling_order(&backend, &[1, 2, 3]), [3, 1, 2]);
+
+    run(&mut manager, Command::MoveToLast).await;
+    assert_eq!(tiling_order(&backend, &[1, 2, 3]), [1, 2, 3]);
+
+    send(&mut manager, &backend, WindowFocused(WindowId(2))).await;
+    run(&mut manager, Command::SwapMain).await;
+    assert_eq!(tiling_order(&backend, &[1, 2, 3]), [2, 1, 3]);
+
+    // Swapping the first window with the main one takes the second
+    run(&mut manager, Command::SwapMain).await;
+    assert_eq!(tiling_order(&backend, &[1, 2, 3]), [1, 2, 3]);
+}
+
+/// Send a command over a real socket and run what the server passes on.
+async fn ipc(
+    manager: &mut WindowManager,
+    client: &IpcClient,
+    commands: &mut mpsc::Receiver<Command>,
+    command: &str,
+    args: &[&str],
+) {
+    let args = args.iter().map(|arg| arg.to_string()).collect();
+    let response = client.send_command(command, args).await.unwrap();
+    assert!(response.success, "{}: {}", command, response.message);
+    run(manager, commands.recv().await.unwrap()).await;
+}
+
+#[tokio::test]
+async fn ipc_changes_the_order() {
+    let (mut manager, backend) = start(stack()).await;
+    open(&mut manager, &backend, &[1, 2, 3]).await;
+    send(&mut manager, &backend, WindowFocused(WindowId(3))).await;
+
+    let mut config = Config::default().ipc;
+    config.socket_path = std::env::temp_dir()
+        .join(format!("skew-order-{}.sock", std::process::id()))
+        .to_string_lossy()
+        .into_owned();
+    let (command_tx, mut commands) = mpsc::channel(8);
+    let server = IpcServer::new(&config, command_tx).await.unwrap();
+    server.start().await.unwrap();
+    let client = IpcClient::new(config.socket_path.clone());
+
+    ipc(&mut manager, &client, &mut commands, "move-first", &[]).await;
+    assert_eq!(tiling_order(&backend, &[1, 2, 3]), [3, 1, 2]);
+
+    ipc(&mut manager, &client, &mut commands, "move-last", &[]).await;
+    assert_eq!(tiling_order(&backend, &[1, 2, 3]), [1, 2, 3]);
+
+    ipc(&mut manager, &client, &mut commands, "swap-main", &[]).await;
+    assert_eq!(tiling_order(&backend, &[1, 2, 3]), [3, 2, 1]);
+
+    ipc(&mut manager, &client, &mut commands, "swap", &["2", "1"]).await;
+    assert_eq!(tiling_order(&backend, &[1, 2, 3]), [3, 1, 2]);
+    std::fs::remove_file(&config.socket_path).unwrap();
+}